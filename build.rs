@@ -1,4 +1,4 @@
-use std::process::Command;
+use std::{fs, process::Command};
 
 fn main() {
     println!("cargo:rustc-link-search=native=library/plgldr/lib");
@@ -12,4 +12,40 @@ fn main() {
         Err(_) => String::from("NOGIT"),
     };
     println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+
+    // Listing files stops cargo from rerunning this on every change, so the commit has to be
+    // watched by hand to keep GIT_HASH current
+    for path in [
+        "build.rs",
+        ".git/HEAD",
+        ".git/refs",
+        "gfx/text",
+        "gfx/compat.toml",
+    ] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    check_compat();
+}
+
+/// Makes sure the compatibility table can be read, since it's built into Barista
+fn check_compat() {
+    let file = fs::read_to_string("gfx/compat.toml").expect("Missing gfx/compat.toml");
+    let table: toml::Table =
+        toml::from_str(&file).unwrap_or_else(|e| panic!("gfx/compat.toml: {}", e));
+
+    for (region, values) in table {
+        for key in ["versions", "updates"] {
+            match values.get(key) {
+                // Title versions are u16s, anything else would only fail once Barista starts
+                Some(toml::Value::Array(c))
+                    if c.iter()
+                        .all(|c| c.as_integer().is_some_and(|c| u16::try_from(c).is_ok())) => {}
+                None if key == "updates" => {}
+                _ => panic!(
+                    "gfx/compat.toml: {}.{} should be a list of versions from 0 to 65535",
+                    region, key
+                ),
+            }
+        }
+    }
 }
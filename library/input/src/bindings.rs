@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Keys;
+
+/// A physical button, as written in the bindings table of cfg.toml
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    ZL,
+    ZR,
+    Start,
+    Select,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    CPadUp,
+    CPadDown,
+    CPadLeft,
+    CPadRight,
+    CStickUp,
+    CStickDown,
+    CStickLeft,
+    CStickRight,
+}
+
+impl Button {
+    pub fn key(self) -> Keys {
+        match self {
+            Self::A => Keys::A,
+            Self::B => Keys::B,
+            Self::X => Keys::X,
+            Self::Y => Keys::Y,
+            Self::L => Keys::L,
+            Self::R => Keys::R,
+            Self::ZL => Keys::ZL,
+            Self::ZR => Keys::ZR,
+            Self::Start => Keys::START,
+            Self::Select => Keys::SELECT,
+            Self::DPadUp => Keys::DPAD_UP,
+            Self::DPadDown => Keys::DPAD_DOWN,
+            Self::DPadLeft => Keys::DPAD_LEFT,
+            Self::DPadRight => Keys::DPAD_RIGHT,
+            Self::CPadUp => Keys::CPAD_UP,
+            Self::CPadDown => Keys::CPAD_DOWN,
+            Self::CPadLeft => Keys::CPAD_LEFT,
+            Self::CPadRight => Keys::CPAD_RIGHT,
+            Self::CStickUp => Keys::CSTICK_UP,
+            Self::CStickDown => Keys::CSTICK_DOWN,
+            Self::CStickLeft => Keys::CSTICK_LEFT,
+            Self::CStickRight => Keys::CSTICK_RIGHT,
+        }
+    }
+}
+
+/// Something the menu can be told to do with a button
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Exit,
+    SlotNames,
+    FastScroll,
+    PrevPage,
+    NextPage,
+    Undo,
+    Profile,
+    /// Held while Barista starts
+    QuickLaunch,
+    Log,
+    /// Has to be held down along with [`Binding::Log`], all of it at once
+    LogHold,
+}
+
+impl Binding {
+    pub const ALL: &'static [Self] = &[
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Confirm,
+        Self::Back,
+        Self::Exit,
+        Self::SlotNames,
+        Self::FastScroll,
+        Self::PrevPage,
+        Self::NextPage,
+        Self::Undo,
+        Self::Profile,
+        Self::QuickLaunch,
+        Self::Log,
+        Self::LogHold,
+    ];
+
+    /// Whether this is used in the menu, where no two can share a button
+    fn in_menu(self) -> bool {
+        !matches!(self, Self::QuickLaunch | Self::LogHold)
+    }
+}
+
+/// Which buttons trigger each [`Binding`]. Any of the listed buttons works
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Bindings {
+    pub up: Vec<Button>,
+    pub down: Vec<Button>,
+    pub left: Vec<Button>,
+    pub right: Vec<Button>,
+    pub confirm: Vec<Button>,
+    pub back: Vec<Button>,
+    pub exit: Vec<Button>,
+    pub slot_names: Vec<Button>,
+    pub fast_scroll: Vec<Button>,
+    pub prev_page: Vec<Button>,
+    pub next_page: Vec<Button>,
+    pub undo: Vec<Button>,
+    pub profile: Vec<Button>,
+    pub quick_launch: Vec<Button>,
+    pub log: Vec<Button>,
+    pub log_hold: Vec<Button>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            up: vec![Button::DPadUp, Button::CPadUp],
+            down: vec![Button::DPadDown, Button::CPadDown],
+            left: vec![Button::DPadLeft, Button::CPadLeft],
+            right: vec![Button::DPadRight, Button::CPadRight],
+            confirm: vec![Button::A],
+            back: vec![Button::B],
+            exit: vec![Button::Start],
+            slot_names: vec![Button::Y],
+            fast_scroll: vec![Button::X],
+            prev_page: vec![Button::L],
+            next_page: vec![Button::R],
+            undo: vec![Button::ZL],
+            profile: vec![Button::ZR],
+            quick_launch: vec![Button::L],
+            log: vec![Button::Select],
+            // Release builds keep the log out of the way
+            log_hold: if cfg!(debug_assertions) {
+                vec![]
+            } else {
+                vec![Button::L, Button::R]
+            },
+        }
+    }
+}
+
+impl Bindings {
+    pub fn buttons(&self, binding: Binding) -> &[Button] {
+        match binding {
+            Binding::Up => &self.up,
+            Binding::Down => &self.down,
+            Binding::Left => &self.left,
+            Binding::Right => &self.right,
+            Binding::Confirm => &self.confirm,
+            Binding::Back => &self.back,
+            Binding::Exit => &self.exit,
+            Binding::SlotNames => &self.slot_names,
+            Binding::FastScroll => &self.fast_scroll,
+            Binding::PrevPage => &self.prev_page,
+            Binding::NextPage => &self.next_page,
+            Binding::Undo => &self.undo,
+            Binding::Profile => &self.profile,
+            Binding::QuickLaunch => &self.quick_launch,
+            Binding::Log => &self.log,
+            Binding::LogHold => &self.log_hold,
+        }
+    }
+
+    pub fn keys(&self, binding: Binding) -> Keys {
+        self.buttons(binding)
+            .iter()
+            .fold(Keys::empty(), |keys, c| keys | c.key())
+    }
+
+    /// Whether any of the buttons for `binding` are in `keys`
+    pub fn matches(&self, binding: Binding, keys: Keys) -> bool {
+        keys.intersects(self.keys(binding))
+    }
+
+    /// Whether all of the buttons for `binding` are in `keys`
+    pub fn matches_all(&self, binding: Binding, keys: Keys) -> bool {
+        keys.contains(self.keys(binding))
+    }
+
+    /// What's wrong with these bindings, if anything: a binding without buttons can't be
+    /// used, and a button shared by two menu bindings only ever does one of them
+    pub fn problem(&self) -> Option<String> {
+        let mut used: Vec<(Button, Binding)> = vec![];
+        for &binding in Binding::ALL {
+            let buttons = self.buttons(binding);
+            if buttons.is_empty() && binding != Binding::LogHold {
+                return Some(format!("{:?} has no buttons", binding));
+            }
+            if !binding.in_menu() {
+                continue;
+            }
+            for &button in buttons {
+                if let Some((_, other)) = used.iter().find(|c| c.0 == button) {
+                    return Some(format!(
+                        "{:?} is bound to both {:?} and {:?}",
+                        button, other, binding
+                    ));
+                }
+                used.push((button, binding));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_fine() {
+        assert_eq!(Bindings::default().problem(), None);
+    }
+
+    #[test]
+    fn empty_binding() {
+        let bindings = Bindings {
+            confirm: vec![],
+            ..Default::default()
+        };
+        assert!(bindings.problem().unwrap().contains("Confirm"));
+    }
+
+    #[test]
+    fn shared_button() {
+        let bindings = Bindings {
+            back: vec![Button::A],
+            ..Default::default()
+        };
+        assert!(bindings.problem().unwrap().contains("Confirm"));
+    }
+
+    #[test]
+    fn quick_launch_can_share() {
+        // L is also the previous page button by default
+        let bindings = Bindings {
+            quick_launch: vec![Button::L],
+            log_hold: vec![Button::L, Button::R],
+            ..Default::default()
+        };
+        assert_eq!(bindings.problem(), None);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Binding, Bindings, Keys};
+
+/// Repeats the direction bindings while they're held down, like a keyboard would
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct HoldController {
+    pub up: Option<u32>,
+    pub down: Option<u32>,
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+}
+
+impl HoldController {
+    const FIRST_PRESS_TIME: u32 = 15;
+    const LOOP_PRESS_TIME: u32 = 4;
+
+    pub fn update(&mut self, keys: Keys, bindings: &Bindings) {
+        for (binding, timer) in [
+            (Binding::Up, &mut self.up),
+            (Binding::Down, &mut self.down),
+            (Binding::Left, &mut self.left),
+            (Binding::Right, &mut self.right),
+        ] {
+            if bindings.matches(binding, keys) {
+                if let Some(c) = timer {
+                    *c += 1;
+                } else {
+                    *timer = Some(0)
+                }
+            } else {
+                *timer = None;
+            }
+        }
+    }
+
+    pub fn should_click(&self, binding: Binding) -> bool {
+        let check = |t| t == 0 || (t >= Self::FIRST_PRESS_TIME && t % Self::LOOP_PRESS_TIME == 0);
+
+        let timer = match binding {
+            Binding::Up => self.up,
+            Binding::Down => self.down,
+            Binding::Left => self.left,
+            Binding::Right => self.right,
+            _ => None,
+        };
+        timer.is_some_and(check)
+    }
+
+    /// Whether `binding` has been held since before this frame
+    pub fn is_repeating(&self, binding: Binding) -> bool {
+        let timer = match binding {
+            Binding::Up => self.up,
+            Binding::Down => self.down,
+            Binding::Left => self.left,
+            Binding::Right => self.right,
+            _ => None,
+        };
+        timer.is_some_and(|t| t > 0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct TouchController {
+    start: Option<(u16, u16)>,
+    last: (u16, u16),
+    dragging: bool,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum TouchEvent {
+    /// The stylus was lifted without moving much, at the given position
+    Tap(u16, u16),
+    /// The stylus moved vertically by this many pixels since last frame
+    Drag(i32),
+}
+
+impl TouchController {
+    const DRAG_THRESHOLD: u16 = 6;
+
+    pub fn update(&mut self, touching: bool, pos: (u16, u16)) -> Option<TouchEvent> {
+        if !touching {
+            // The touch position reads as (0, 0) once the stylus is lifted, so use the last one
+            let event = match self.start {
+                Some(_) if !self.dragging => Some(TouchEvent::Tap(self.last.0, self.last.1)),
+                _ => None,
+            };
+            *self = Self::default();
+            return event;
+        }
+
+        let Some(start) = self.start else {
+            self.start = Some(pos);
+            self.last = pos;
+            return None;
+        };
+
+        if !self.dragging && pos.1.abs_diff(start.1) > Self::DRAG_THRESHOLD {
+            self.dragging = true;
+        }
+        let dy = pos.1 as i32 - self.last.1 as i32;
+        self.last = pos;
+        (self.dragging && dy != 0).then_some(TouchEvent::Drag(dy))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hold_repeats_after_a_delay() {
+        let bindings = Bindings::default();
+        let mut hold = HoldController::default();
+        let clicks: Vec<_> = (0..24)
+            .map(|_| {
+                hold.update(Keys::DPAD_DOWN, &bindings);
+                hold.should_click(Binding::Down)
+            })
+            .collect();
+        let frames: Vec<_> = (0..24).filter(|c| clicks[*c]).collect();
+        assert_eq!(frames, [0, 16, 20]);
+        assert!(hold.is_repeating(Binding::Down));
+        assert!(!hold.should_click(Binding::Up));
+
+        hold.update(Keys::empty(), &bindings);
+        assert!(!hold.should_click(Binding::Down));
+        assert!(!hold.is_repeating(Binding::Down));
+    }
+
+    #[test]
+    fn tap_is_reported_on_release() {
+        let mut touch = TouchController::default();
+        assert_eq!(touch.update(true, (100, 50)), None);
+        assert_eq!(touch.update(true, (102, 52)), None);
+        // Lifting the stylus reads as (0, 0)
+        assert_eq!(touch.update(false, (0, 0)), Some(TouchEvent::Tap(102, 52)));
+    }
+
+    #[test]
+    fn drag_is_not_a_tap() {
+        let mut touch = TouchController::default();
+        touch.update(true, (100, 50));
+        assert_eq!(touch.update(true, (100, 60)), Some(TouchEvent::Drag(10)));
+        assert_eq!(touch.update(true, (100, 60)), None);
+        assert_eq!(touch.update(true, (100, 55)), Some(TouchEvent::Drag(-5)));
+        assert_eq!(touch.update(false, (0, 0)), None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::ops::{BitOr, BitOrAssign, Sub};
+
+/// A set of buttons, laid out like the HID key bitfield libctru reads, so `KeyPad::bits` goes
+/// straight into [`Keys::from_bits`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Keys(u32);
+
+impl Keys {
+    pub const A: Self = Self(1 << 0);
+    pub const B: Self = Self(1 << 1);
+    pub const SELECT: Self = Self(1 << 2);
+    pub const START: Self = Self(1 << 3);
+    pub const DPAD_RIGHT: Self = Self(1 << 4);
+    pub const DPAD_LEFT: Self = Self(1 << 5);
+    pub const DPAD_UP: Self = Self(1 << 6);
+    pub const DPAD_DOWN: Self = Self(1 << 7);
+    pub const R: Self = Self(1 << 8);
+    pub const L: Self = Self(1 << 9);
+    pub const X: Self = Self(1 << 10);
+    pub const Y: Self = Self(1 << 11);
+    pub const ZL: Self = Self(1 << 14);
+    pub const ZR: Self = Self(1 << 15);
+    pub const TOUCH: Self = Self(1 << 20);
+    pub const CSTICK_RIGHT: Self = Self(1 << 24);
+    pub const CSTICK_LEFT: Self = Self(1 << 25);
+    pub const CSTICK_UP: Self = Self(1 << 26);
+    pub const CSTICK_DOWN: Self = Self(1 << 27);
+    pub const CPAD_RIGHT: Self = Self(1 << 28);
+    pub const CPAD_LEFT: Self = Self(1 << 29);
+    pub const CPAD_UP: Self = Self(1 << 30);
+    pub const CPAD_DOWN: Self = Self(1 << 31);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every key in `other` is in here too
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether any key in `other` is in here too
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for Keys {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Keys {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+impl Sub for Keys {
+    type Output = Self;
+
+    /// The keys in `self` that aren't in `rhs`
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
@@ -0,0 +1,12 @@
+// Buttons, bindings and the state machines that turn them into menu input. Kept apart from
+// the rest of Barista so none of it needs libctru, and its tests run on the host
+
+mod bindings;
+mod controller;
+mod keys;
+mod script;
+
+pub use bindings::{Binding, Bindings, Button};
+pub use controller::{HoldController, TouchController, TouchEvent};
+pub use keys::Keys;
+pub use script::{Frame, ScriptedInput};
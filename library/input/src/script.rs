@@ -0,0 +1,106 @@
+use crate::Keys;
+
+/// One frame of input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub held: Keys,
+    /// Stylus position, if the screen is being touched
+    pub touch: Option<(u16, u16)>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::keys(Keys::empty())
+    }
+}
+
+impl Frame {
+    pub fn keys(held: Keys) -> Self {
+        Self { held, touch: None }
+    }
+
+    pub fn touch(x: u16, y: u16) -> Self {
+        Self {
+            held: Keys::TOUCH,
+            touch: Some((x, y)),
+        }
+    }
+}
+
+/// Plays back a list of frames, one per call to [`ScriptedInput::advance`]
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput {
+    frames: Vec<Frame>,
+    current: usize,
+    previous: Frame,
+}
+
+impl ScriptedInput {
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Self {
+            frames,
+            current: 0,
+            previous: Frame::default(),
+        }
+    }
+
+    /// Presses and releases each button in order, one frame each
+    pub fn presses(keys: &[Keys]) -> Self {
+        Self::new(
+            keys.iter()
+                .flat_map(|c| [Frame::keys(*c), Frame::default()])
+                .collect(),
+        )
+    }
+
+    fn frame(&self) -> Frame {
+        self.frames.get(self.current).copied().unwrap_or_default()
+    }
+
+    /// Moves to the next frame. Returns false once the script has run out
+    pub fn advance(&mut self) -> bool {
+        self.previous = self.frame();
+        self.current += 1;
+        self.current < self.frames.len()
+    }
+
+    pub fn keys_down(&self) -> Keys {
+        self.frame().held - self.previous.held
+    }
+
+    pub fn keys_held(&self) -> Keys {
+        self.frame().held
+    }
+
+    pub fn touch_position(&self) -> (u16, u16) {
+        self.frame().touch.unwrap_or((0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_keys_are_only_down_once() {
+        let held = Frame::keys(Keys::A);
+        let mut input = ScriptedInput::new(vec![held, held, Frame::default()]);
+        assert_eq!(input.keys_down(), Keys::A);
+        assert!(input.advance());
+        assert_eq!(input.keys_down(), Keys::empty());
+        assert_eq!(input.keys_held(), Keys::A);
+        assert!(input.advance());
+        assert_eq!(input.keys_held(), Keys::empty());
+        assert!(!input.advance());
+    }
+
+    #[test]
+    fn presses_release_in_between() {
+        let mut input = ScriptedInput::presses(&[Keys::A, Keys::A]);
+        let mut down = vec![input.keys_down()];
+        while input.advance() {
+            down.push(input.keys_down());
+        }
+        assert_eq!(down, [Keys::A, Keys::empty(), Keys::A, Keys::empty()]);
+    }
+}
@@ -14,4 +14,7 @@ Result  plgLdrInit(void);
 void    plgLdrExit(void);
 Result  PLGLDR__IsPluginLoaderEnabled(bool *isEnabled);
 Result  PLGLDR__SetPluginLoaderState(bool enabled);
-Result  PLGLDR__SetPluginLoadParameters(PluginLoadParameters *parameters);
\ No newline at end of file
+Result  PLGLDR__SetPluginLoadParameters(PluginLoadParameters *parameters);
+Result  PLGLDR__GetVersion(u32 *version);
+Result  PLGLDR__GetArenaSize(u32 *size);
+Result  PLGLDR__GetPluginPath(char *path);
\ No newline at end of file
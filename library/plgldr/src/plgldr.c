@@ -10,6 +10,9 @@ Result  plgLdrInit(void)
 
     if (AtomicPostIncrement(&plgLdrRefCount) == 0)
         res = svcConnectToPort(&plgLdrHandle, "plg:ldr");
+    // Nothing to close, so plgLdrExit won't be called for this one
+    if (R_FAILED(res))
+        AtomicDecrement(&plgLdrRefCount);
     return res;
 }
 
@@ -65,6 +68,53 @@ Result  PLGLDR__SetPluginLoadParameters(PluginLoadParameters *parameters)
     cmdbuf[5] = IPC_Desc_Buffer(32 * sizeof(u32), IPC_BUFFER_R);
     cmdbuf[6] = (u32)parameters->config;
 
+    if (R_SUCCEEDED((res = svcSendSyncRequest(plgLdrHandle))))
+    {
+        res = cmdbuf[1];
+    }
+    return res;
+}
+
+Result  PLGLDR__GetVersion(u32 *version)
+{
+    Result res = 0;
+
+    u32 *cmdbuf = getThreadCommandBuffer();
+
+    cmdbuf[0] = IPC_MakeHeader(8, 0, 0);
+    if (R_SUCCEEDED((res = svcSendSyncRequest(plgLdrHandle))))
+    {
+        res = cmdbuf[1];
+        *version = cmdbuf[2];
+    }
+    return res;
+}
+
+Result  PLGLDR__GetArenaSize(u32 *size)
+{
+    Result res = 0;
+
+    u32 *cmdbuf = getThreadCommandBuffer();
+
+    cmdbuf[0] = IPC_MakeHeader(9, 0, 0);
+    if (R_SUCCEEDED((res = svcSendSyncRequest(plgLdrHandle))))
+    {
+        res = cmdbuf[1];
+        *size = cmdbuf[2];
+    }
+    return res;
+}
+
+Result  PLGLDR__GetPluginPath(char *path)
+{
+    Result res = 0;
+
+    u32 *cmdbuf = getThreadCommandBuffer();
+
+    cmdbuf[0] = IPC_MakeHeader(10, 0, 2);
+    cmdbuf[1] = IPC_Desc_Buffer(255, IPC_BUFFER_RW);
+    cmdbuf[2] = (u32)path;
+
     if (R_SUCCEEDED((res = svcSendSyncRequest(plgLdrHandle))))
     {
         res = cmdbuf[1];
@@ -0,0 +1,64 @@
+use crate::{Object, Rectangle, Text};
+
+/// A labelled rectangle that can be tapped. Hidden buttons aren't drawn and can't be touched
+pub struct Button {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub color: u32,
+    pub visible: bool,
+    label: Text,
+}
+
+impl Button {
+    pub fn new(label: String, x: u16, y: u16, width: u16, height: u16, size: u8) -> Self {
+        let mut out = Self {
+            x,
+            y,
+            width,
+            height,
+            color: 0xFFD0D0D0,
+            visible: true,
+            label: Text::new(label, x, y, size),
+        };
+        out.center_label();
+        out
+    }
+
+    pub fn set_label(&mut self, label: String) {
+        self.label.change_text(label);
+        self.center_label();
+    }
+
+    fn center_label(&mut self) {
+        self.label.x = self.x + self.width.saturating_sub(self.label.width()) / 2;
+        self.label.y = self.y + self.height.saturating_sub(self.label.height()) / 2;
+    }
+}
+
+impl Object for Button {
+    fn draw(&self) -> bool {
+        if !self.visible {
+            return true;
+        }
+        Rectangle {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            color: self.color,
+            depth: 0.0,
+        }
+        .draw()
+            && self.label.draw()
+    }
+
+    fn touch_region(&self) -> Option<(u16, u16, u16, u16)> {
+        if self.visible {
+            Some((self.x, self.y, self.width, self.height))
+        } else {
+            None
+        }
+    }
+}
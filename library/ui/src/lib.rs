@@ -6,9 +6,13 @@ use citro2d_sys::{
 #[macro_use]
 extern crate mopa;
 
+pub mod button;
+pub mod list;
 pub mod sprite;
 pub mod text;
 
+pub use button::Button;
+pub use list::List;
 pub use text::Text;
 
 #[repr(u32)]
@@ -104,6 +108,11 @@ impl BaristaUI {
         }
     }
 
+    /// Name of the topmost object on `screen` whose touch region contains the given point
+    pub fn touched_object(&self, screen: Screen, x: u16, y: u16) -> Option<&'static str> {
+        self.get_scene(screen)?.touched_object(x, y)
+    }
+
     pub fn downcast_object<T: Object>(&self, screen: Screen, name: &str) -> Option<&T> {
         match screen {
             Screen::Top => self
@@ -213,10 +222,32 @@ impl Scene {
         }
         None
     }
+
+    /// Objects are drawn in order, so the last one that was added is the one on top
+    pub fn touched_object(&self, x: u16, y: u16) -> Option<&'static str> {
+        for (name, object) in self.objects.iter().rev() {
+            if object.is_touched(x, y) {
+                return Some(name);
+            }
+        }
+        None
+    }
 }
 
 pub trait Object: mopa::Any {
     fn draw(&self) -> bool;
+
+    /// Area of the screen that reacts to touch, as (x, y, width, height)
+    fn touch_region(&self) -> Option<(u16, u16, u16, u16)> {
+        None
+    }
+
+    fn is_touched(&self, x: u16, y: u16) -> bool {
+        match self.touch_region() {
+            Some((rx, ry, w, h)) => (rx..rx + w).contains(&x) && (ry..ry + h).contains(&y),
+            None => false,
+        }
+    }
 }
 
 mopafy!(Object);
@@ -245,6 +276,34 @@ impl Object for StaticObject {
     }
 }
 
+/// A solid-colored rectangle
+pub struct Rectangle {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub color: u32,
+    pub depth: f32,
+}
+
+impl Object for Rectangle {
+    fn draw(&self) -> bool {
+        unsafe {
+            citro2d_sys::C2D_DrawRectangle(
+                self.x as f32,
+                self.y as f32,
+                self.depth,
+                self.width as f32,
+                self.height as f32,
+                self.color,
+                self.color,
+                self.color,
+                self.color,
+            )
+        }
+    }
+}
+
 /*
 /// An Object with multiple sprites associated, which can be switched between
 pub struct MultiSpriteObj {
@@ -0,0 +1,183 @@
+use crate::{Object, Rectangle, Text};
+
+/// A vertical list of text rows with a highlighted cursor and scroll indicators
+pub struct List {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub row_height: u16,
+    pub visible_rows: usize,
+    pub size: u8,
+    pub highlight_color: u32,
+    pub arrow_color: u32,
+    cursor: Option<usize>,
+    scroll: usize,
+    drag_offset: i32,
+    items: Vec<String>,
+    /// Text for the rows on screen, from `scroll` on. citro2d text buffers aren't cheap, so
+    /// there's never more than `visible_rows` of them
+    rows: Vec<Text>,
+}
+
+impl List {
+    pub fn new(x: u16, y: u16, width: u16, row_height: u16, visible_rows: usize, size: u8) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            row_height,
+            visible_rows,
+            size,
+            highlight_color: 0xFF9AD8FF,
+            arrow_color: 0xFF404040,
+            cursor: None,
+            scroll: 0,
+            drag_offset: 0,
+            items: vec![],
+            rows: vec![],
+        }
+    }
+
+    /// Replaces the contents of the list. Only the rows that end up on screen get text
+    pub fn set_items(&mut self, items: &[String]) {
+        self.items = items.to_vec();
+        self.set_cursor(self.cursor);
+    }
+
+    /// Moves the cursor, scrolling just enough to keep it visible. With no cursor, the
+    /// list stays scrolled to the bottom
+    pub fn set_cursor(&mut self, cursor: Option<usize>) {
+        self.cursor = cursor.filter(|c| *c < self.items.len());
+        match self.cursor {
+            Some(c) if c < self.scroll => self.scroll = c,
+            Some(c) if c >= self.scroll + self.visible_rows => {
+                self.scroll = c + 1 - self.visible_rows
+            }
+            Some(_) => self.scroll = self.scroll.min(self.max_scroll()),
+            None => self.scroll = self.max_scroll(),
+        }
+        self.layout();
+    }
+
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Index of the item at the given point, if there's one
+    pub fn row_at(&self, x: u16, y: u16) -> Option<usize> {
+        if !self.is_touched(x, y) {
+            return None;
+        }
+        let row = self.scroll + ((y - self.y) / self.row_height) as usize;
+        (row < self.items.len()).then_some(row)
+    }
+
+    /// Scrolls the list by however many rows `dy` pixels add up to, so that the contents
+    /// follow the stylus
+    pub fn drag(&mut self, dy: i32) {
+        self.drag_offset += dy;
+        let rows = self.drag_offset / self.row_height as i32;
+        if rows == 0 {
+            return;
+        }
+        self.drag_offset -= rows * self.row_height as i32;
+        self.scroll = (self.scroll as i32 - rows).clamp(0, self.max_scroll() as i32) as usize;
+        self.layout();
+    }
+
+    pub fn max_scroll(&self) -> usize {
+        self.items.len().saturating_sub(self.visible_rows)
+    }
+
+    /// Points the on-screen rows at the items from `scroll` on, reusing the already allocated
+    /// text buffers
+    fn layout(&mut self) {
+        let end = self.items.len().min(self.scroll + self.visible_rows);
+        let visible = &self.items[self.scroll.min(end)..end];
+        self.rows.truncate(visible.len());
+        for (i, item) in visible.iter().enumerate() {
+            // citro2d can't allocate empty text buffers
+            let item = if item.is_empty() { " " } else { item.as_str() };
+            let y = self.y + i as u16 * self.row_height;
+            match self.rows.get_mut(i) {
+                Some(c) => {
+                    c.change_text(item.to_string());
+                    c.x = self.x + 4;
+                    c.y = y;
+                }
+                None => self
+                    .rows
+                    .push(Text::new(item.to_string(), self.x + 4, y, self.size)),
+            }
+        }
+    }
+
+    fn draw_arrow(&self, y: f32, up: bool) {
+        let x = (self.x + self.width) as f32 - 10.0;
+        let (base, point) = if up { (y + 6.0, y) } else { (y, y + 6.0) };
+        unsafe {
+            citro2d_sys::C2D_DrawTriangle(
+                x - 5.0,
+                base,
+                self.arrow_color,
+                x + 5.0,
+                base,
+                self.arrow_color,
+                x,
+                point,
+                self.arrow_color,
+                0.0,
+            );
+        }
+    }
+}
+
+impl Object for List {
+    fn draw(&self) -> bool {
+        let mut out = true;
+        for (i, row) in self.rows.iter().enumerate() {
+            if self.cursor == Some(self.scroll + i) {
+                Rectangle {
+                    x: self.x,
+                    y: row.y,
+                    width: self.width,
+                    height: self.row_height,
+                    color: self.highlight_color,
+                    depth: 0.0,
+                }
+                .draw();
+            }
+            out &= row.draw();
+        }
+
+        if self.scroll > 0 {
+            self.draw_arrow(self.y as f32 + 2.0, true);
+        }
+        if self.scroll + self.rows.len() < self.items.len() {
+            let bottom = self.y + self.visible_rows as u16 * self.row_height;
+            self.draw_arrow(bottom as f32 - 8.0, false);
+        }
+        out
+    }
+
+    fn touch_region(&self) -> Option<(u16, u16, u16, u16)> {
+        Some((
+            self.x,
+            self.y,
+            self.width,
+            self.visible_rows as u16 * self.row_height,
+        ))
+    }
+}
@@ -0,0 +1,128 @@
+// Safe wrapper over the parts of the AM service Barista needs, which is just looking up
+// what titles are installed. ctru-rs doesn't have GetTitleInfo yet
+
+use ctru::error::Error as CtruError;
+use ctru_sys::{
+    amExit, amInit, AM_GetTitleCount, AM_GetTitleInfo, AM_GetTitleList, AM_TitleEntry,
+    FS_MediaType, MEDIATYPE_GAME_CARD, MEDIATYPE_SD, R_FAILED,
+};
+
+use crate::Result;
+
+/// Where a title can be installed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Media {
+    Sd,
+    GameCard,
+}
+
+impl Media {
+    fn raw(self) -> FS_MediaType {
+        match self {
+            Self::Sd => MEDIATYPE_SD,
+            Self::GameCard => MEDIATYPE_GAME_CARD,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TitleInfo {
+    /// Title version, as shown by FBI and friends
+    pub version: u16,
+}
+
+/// Somewhere to look up installed titles. The console's is [`Am`], tests use a made up
+/// one instead
+pub trait TitleDb {
+    /// IDs of every title installed on `media`
+    fn list(&self, media: Media) -> Result<Vec<u64>>;
+
+    /// Info on title `id`, or None if it isn't installed on `media`
+    fn info(&self, media: Media, id: u64) -> Option<TitleInfo>;
+}
+
+/// Handle to the AM service, which is closed again when dropped
+pub struct Am(());
+
+impl Am {
+    pub fn new() -> Result<Self> {
+        let res = unsafe { amInit() };
+        if R_FAILED(res) {
+            Err(CtruError::Os(res))?;
+        }
+        Ok(Self(()))
+    }
+}
+
+impl Drop for Am {
+    fn drop(&mut self) {
+        unsafe { amExit() };
+    }
+}
+
+impl TitleDb for Am {
+    fn list(&self, media: Media) -> Result<Vec<u64>> {
+        let mut count = 0;
+        let res = unsafe { AM_GetTitleCount(media.raw(), &mut count) };
+        if R_FAILED(res) {
+            Err(CtruError::Os(res))?;
+        }
+
+        let mut ids = vec![0u64; count as usize];
+        let mut read = 0;
+        let res = unsafe { AM_GetTitleList(&mut read, media.raw(), count, ids.as_mut_ptr()) };
+        if R_FAILED(res) {
+            Err(CtruError::Os(res))?;
+        }
+        ids.truncate(read as usize);
+        Ok(ids)
+    }
+
+    fn info(&self, media: Media, id: u64) -> Option<TitleInfo> {
+        let mut id = id;
+        let mut entry = AM_TitleEntry::default();
+        let res = unsafe { AM_GetTitleInfo(media.raw(), 1, &mut id, &mut entry) };
+        (!R_FAILED(res)).then_some(TitleInfo {
+            version: entry.version,
+        })
+    }
+}
+
+/// A made up title database, for checking version detection without a console
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeTitleDb {
+    titles: Vec<(Media, u64, TitleInfo)>,
+}
+
+#[cfg(test)]
+impl FakeTitleDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `id` to `media` with the given version
+    pub fn install(mut self, media: Media, id: u64, version: u16) -> Self {
+        self.titles.push((media, id, TitleInfo { version }));
+        self
+    }
+}
+
+#[cfg(test)]
+impl TitleDb for FakeTitleDb {
+    fn list(&self, media: Media) -> Result<Vec<u64>> {
+        Ok(self
+            .titles
+            .iter()
+            .filter(|(m, ..)| *m == media)
+            .map(|(_, id, _)| *id)
+            .collect())
+    }
+
+    fn info(&self, media: Media, id: u64) -> Option<TitleInfo> {
+        self.titles
+            .iter()
+            .find(|(m, i, _)| *m == media && *i == id)
+            .map(|(.., c)| *c)
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    fs,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+use ui_lib::BaristaUI;
+
+#[cfg(feature = "audio")]
+use crate::audio::AudioManager;
+use crate::{
+    crash,
+    format::{barista_cfg::BaristaConfig, saltwater_cfg::Config},
+    launcher::{Conflict, GameVer},
+    saltwater, Result,
+};
+
+pub const SETTINGS_PATH: &str = "sdmc:/spicerack/cfg.toml";
+pub const CONFIG_PATH: &str = "sdmc:/spicerack/bin/saltwater.cfg";
+
+pub const PROFILES_DIR: &str = "sdmc:/spicerack/profiles";
+
+/// Where the mod slots for `profile` are stored, the default profile being saltwater.cfg.
+/// Saltwater itself only ever reads saltwater.cfg, see [`crate::launcher::use_profile`]
+pub fn config_path(profile: Option<&str>) -> String {
+    match profile {
+        Some(c) => format!("{}/{}.cfg", PROFILES_DIR, c),
+        None => CONFIG_PATH.to_string(),
+    }
+}
+
+/// Mod profiles with a file in the profiles folder, sorted
+pub fn find_profiles() -> Vec<String> {
+    let Ok(dir) = fs::read_dir(PROFILES_DIR) else {
+        return vec![];
+    };
+    let mut out: Vec<_> = dir
+        .flatten()
+        .map(|c| c.path())
+        .filter(|c| c.extension().is_some_and(|c| c == "cfg"))
+        .filter_map(|c| Some(c.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Every profile there is to pick from: the ones on the SD card and the ones some version is
+/// set to, which might not have been saved yet
+pub fn known_profiles(found: &[String], settings: &BaristaConfig) -> Vec<String> {
+    let mut out = found.to_vec();
+    out.extend(settings.profiles());
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// A name for a new profile that isn't taken by any of `existing`
+pub fn new_profile_name(existing: &[String]) -> String {
+    (1..)
+        .map(|i| format!("profile{}", i))
+        .find(|c| !existing.contains(c))
+        .unwrap()
+}
+
+/// Everything Barista keeps around while the menu is up. Owned by `run` and lent out to the
+/// menu and scenes, so nothing needs to be global
+///
+/// `R` is whatever draws the menu, the real UI on console
+pub struct App<R = BaristaUI> {
+    pub ui: R,
+    /// Barista's own settings (cfg.toml)
+    pub settings: Tracked<BaristaConfig>,
+    /// Saltwater's mod slots for the profile being edited
+    pub config: Tracked<Config>,
+    /// Mod profile `config` belongs to, None for the default one (saltwater.cfg)
+    pub profile: Option<String>,
+    /// Mod profiles in the profiles folder, see [`find_profiles`]
+    pub profiles: Vec<String>,
+    /// Earlier states of the mod slots, for undoing edits in Set up mods
+    pub mod_undo: UndoStack,
+    pub versions: Vec<GameVer>,
+    pub mods: Vec<PathBuf>,
+    /// Page of the mod list being shown in Set up mods
+    pub page: usize,
+    /// What's in Luma's folders for the detected versions, and whether to disable it
+    pub conflicts: Vec<Conflict>,
+    /// Installed Saltwater, and any updates for it
+    pub saltwater: saltwater::Status,
+    /// None when there's nothing to play it on, like when running headless
+    #[cfg(feature = "audio")]
+    pub audio: Option<AudioManager>,
+}
+
+impl<R> App<R> {
+    pub fn save_settings(&mut self) -> Result<()> {
+        self.settings.to_file(SETTINGS_PATH)?;
+        self.settings.mark_saved();
+        crash::set_config(&self.settings, &self.config);
+        log!(Info, Config, "Saved settings to {}", SETTINGS_PATH);
+        Ok(())
+    }
+
+    pub fn save_config(&mut self) -> Result<()> {
+        let path = config_path(self.profile.as_deref());
+        if let Some(c) = Path::new(&path).parent() {
+            fs::create_dir_all(c)?;
+        }
+        self.config.to_file(&path)?;
+        self.config.mark_saved();
+        if let Some(c) = &self.profile {
+            if !self.profiles.contains(c) {
+                self.profiles.push(c.clone());
+                self.profiles.sort();
+            }
+        }
+        crash::set_config(&self.settings, &self.config);
+        log!(Info, Config, "Saved mod slots to {}", path);
+        self.mod_undo.clear();
+        Ok(())
+    }
+
+    #[cfg(feature = "audio")]
+    pub fn toggle_audio(&mut self) {
+        if let Some(audio) = &mut self.audio {
+            if audio.is_playing() {
+                audio.pause()
+            } else {
+                audio.play()
+            }
+        }
+    }
+}
+
+/// A config file loaded in memory, along with what was last written to disk, so unsaved
+/// changes can be told apart and thrown away
+pub struct Tracked<T> {
+    current: T,
+    saved: T,
+}
+
+impl<T: Clone + PartialEq> Tracked<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            saved: value.clone(),
+            current: value,
+        }
+    }
+
+    /// Whether there's changes that haven't been saved
+    pub fn is_dirty(&self) -> bool {
+        self.current != self.saved
+    }
+
+    /// Call after writing the file
+    pub fn mark_saved(&mut self) {
+        self.saved = self.current.clone();
+    }
+
+    /// Goes back to what was last saved
+    pub fn discard(&mut self) {
+        self.current = self.saved.clone();
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.current
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.current
+    }
+}
+
+/// The last few states of the mod slots, most recent last
+#[derive(Default)]
+pub struct UndoStack(Vec<HashMap<u16, String>>);
+
+impl UndoStack {
+    const LEN: usize = 16;
+
+    /// Remembers `btks` as it is before an edit
+    pub fn push(&mut self, btks: &HashMap<u16, String>) {
+        if self.0.len() == Self::LEN {
+            self.0.remove(0);
+        }
+        self.0.push(btks.clone());
+    }
+
+    pub fn pop(&mut self) -> Option<HashMap<u16, String>> {
+        self.0.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+}
@@ -1,3 +1,5 @@
+use static_assertions::const_assert;
+
 // Megamix mode
 pub const SLOT_NAMES_DEFAULT: [&str; 0x68] = [
     "Clappy Trio",
@@ -375,16 +377,16 @@ pub const SLOT_NAMES_INFERNAL: [&str; 0x68] = [
     "burn it (fire emoji)",
     "agbAimu",
     "THE MEMES",
-    "unimplemented uwu", //TODO: Glee Club 0
+    "Gleeful Screaming",
     "Ping-pong Any%",
-    "unimplemented uwu", //TODO: Fillbots 0
+    "Hungry Robots",
     "ntrAnime",
-    "unimplemented uwu", //TODO: Air Rally 0
-    "unimplemented uwu", //TODO: Micro-Row 0
-    "unimplemented uwu", //TODO: Flipper Flop 0
+    "Badminton but planes",
+    "Flea Circus",
+    "Seal of approval",
     "Fartless Figure",
-    "unimplemented uwu", //TODO: Fruit Basket 0
-    "unimplemented uwu", //TODO: First Contact
+    "ctrFruitBasket (empty)",
+    "Alien Translator 3000",
     "BDGBbEb",
     "the gay one :)",
     "agbShibafu",
@@ -395,29 +397,29 @@ pub const SLOT_NAMES_INFERNAL: [&str; 0x68] = [
     "agbHandCrap",
     "agb4BeatKeep",
     "rvlGomaA",
-    "Actual Bunny Hop",  //TODO: Bunny Hop
-    "unimplemented uwu", //TODO: Rat Race
+    "Actual Bunny Hop",
+    "Mouse Marathon",
     "rvlShuji",
     "agbTRex",
     "rvlTap (no not that one)",
     "salsa walsa :3",
-    "unimplemented uwu", //TODO: Airboarder (ntrFushigijan?)
+    "ntrFushigijan",
     "Lockstep",
     "World War I",
     "part of ntrAdultMan",
     "Flipper Snapper",
     "ntrMoody",
-    "unimplemented uwu", //TODO: Frog Hop
-    "unimplemented uwu", //TODO: Fan Club
-    "unimplemented uwu", //TODO: Dog Ninja
+    "Frog Bop",
+    "Monkey Simp Club",
+    "Ninja Doggo",
     "ntrRemix8",
-    "unimplemented uwu", //TODO: Return of Fillbots
+    "Hungry Robots 2",
     "Karate Man Kicks! 2",
     "ntrBathRoman",
     "ntrFlea",
     "Polyrhythm Wii",
-    "unimplemented uwu", //TODO: Return of Air Rally
-    "unimplemented uwu", //TODO: Exhibition Match
+    "Badminton but planes 2",
+    "Baseball, kinda",
     "Flockstep",
     "OOOO",
     "A W for you",
@@ -425,25 +427,25 @@ pub const SLOT_NAMES_INFERNAL: [&str; 0x68] = [
     "rvlShugyo",
     "Fork to eat",
     "rvlBaseball",
-    "unimplemented uwu", //TODO: Return of Flipper Flop
+    "Seal of approval 2",
     "THE MEMES 2",
     "OSHA nightmare",
     "Figure Farter",
     "for sure",
     "rvlSex",
     "(lenny face)",
-    "unimplemented uwu", //TODO: Launch Party
+    "rvlRocket go brrr",
     "rvlRotationS",
-    "Samurai Slice",     //TODO: boring
-    "unimplemented uwu", //TODO: See-Saw
+    "Samurai Slice", //TODO: boring
+    "Saw-See",
     "remix10-v2-final.png",
     "Clockstep",
     "If I were green I would die",
-    "unimplemented uwu", //TODO: Animal Acrobat
+    "Zoo Circus Incident",
     "ctrTutorial",
-    "Samurai SIice",     //TODO: ok i get the joke now but still kinda boring
-    "unimplemented uwu", //TODO: Return of Fruit Basket
-    "unimplemented uwu", //TODO: Second Contact
+    "Samurai SIice", //TODO: ok i get the joke now but still kinda boring
+    "ctrFruitBasket (still empty)",
+    "Alien Translator 3001",
     "Love Lab",
     "Luigi's Mansion (2001)",
     "ctrBread",
@@ -453,13 +455,13 @@ pub const SLOT_NAMES_INFERNAL: [&str; 0x68] = [
     "agbTebyoushiDexy",
     "better space dance",
     "AAAAA YES YES YES YES YES Y",
-    "unimplemented uwu", //TODO: Jumpin' Jazz
-    "unimplemented uwu", //TODO: Fan Club 2
+    "Jazz Hands (literally)",
+    "Monkey Simp Club 2",
     "Wrist exterminator",
     "omg she transitioned good f-",
     "           .",
     "Figure Ex-Farter",
-    "unimplemented uwu", //TODO: Jungle Gymnast
+    "Monkey Bars Simulator",
     "<garbled text goes here>",
     "the bad one",
     "the good one",
@@ -500,3 +502,41 @@ pub const SLOT_NAMES_INFERNAL_GATE: [&str; 0x14] = [
     "Pretentious nonsense (asset",
     "chiken (assets)",
 ];
+
+/// Placeholder used while a slot title table is being filled in
+const PLACEHOLDER: &str = "unimplemented uwu";
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn is_complete(table: &[&str]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].is_empty() || str_eq(table[i], PLACEHOLDER) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Make sure no slot title table ships with missing entries
+const_assert!(is_complete(&SLOT_NAMES_DEFAULT));
+const_assert!(is_complete(&SLOT_NAMES_NORETCON));
+const_assert!(is_complete(&SLOT_NAMES_GATE));
+const_assert!(is_complete(&SLOT_NAMES_INTERNAL));
+const_assert!(is_complete(&SLOT_NAMES_INTERNAL_GATE));
+const_assert!(is_complete(&SLOT_NAMES_INFERNAL));
+const_assert!(is_complete(&SLOT_NAMES_INFERNAL_GATE));
@@ -0,0 +1,172 @@
+// Crash reports: whatever's worth knowing when Barista panics or gives up with an error,
+// written to the SD card so people have something to attach to bug reports
+
+use std::{
+    fmt::Write as _,
+    fs,
+    panic::Location,
+    sync::{Mutex, TryLockError},
+};
+
+use backtrace::Backtrace;
+use lazy_static::lazy_static;
+
+use crate::{
+    format::{barista_cfg::BaristaConfig, saltwater_cfg::Config},
+    launcher::GameVer,
+    log::now,
+    Result,
+};
+
+pub const CRASH_DIR: &str = "sdmc:/spicerack/crashes";
+
+/// Features Barista was built with
+const FEATURES: &[(&str, bool)] = &[
+    ("jp", cfg!(feature = "jp")),
+    ("audio", cfg!(feature = "audio")),
+];
+
+/// What Barista was up to, kept up to date as it changes so there's no need to reach the
+/// [`App`](crate::app::App) from a panic
+#[derive(Default)]
+struct Context {
+    games: Vec<String>,
+    settings: Option<String>,
+    mod_slots: Vec<String>,
+}
+
+lazy_static! {
+    static ref CONTEXT: Mutex<Context> = Mutex::new(Context::default());
+}
+
+pub fn set_games(versions: &[GameVer]) {
+    let Ok(mut context) = CONTEXT.lock() else {
+        return;
+    };
+    context.games = versions
+        .iter()
+        .map(|c| {
+            format!(
+                "{} {}, v{}, {}",
+                c.region,
+                if c.is_digital { "digital" } else { "cartridge" },
+                c.version,
+                match c.update {
+                    Some(u) => format!("update v{}", u),
+                    None => "no update".to_string(),
+                }
+            )
+        })
+        .collect();
+}
+
+pub fn set_config(settings: &BaristaConfig, config: &Config) {
+    let Ok(mut context) = CONTEXT.lock() else {
+        return;
+    };
+    context.settings = Some(
+        toml::to_string_pretty(settings).unwrap_or_else(|e| format!("(couldn't save: {})", e)),
+    );
+    let mut slots: Vec<_> = config.btks.iter().collect();
+    slots.sort();
+    context.mod_slots = slots
+        .into_iter()
+        .map(|(slot, name)| format!("{:03X}: {}", slot, name))
+        .collect();
+}
+
+/// Writes a crash report for `message` and returns its path. Panics should pass where
+/// they happened so there's a backtrace to go along with it
+///
+/// This runs from the panic hook, so it stays away from anything the panicking code could
+/// be holding: the context is only read if it's free, and nothing gets logged
+pub fn save(message: &str, location: Option<&Location>) -> Result<String> {
+    let ((year, month, day), (hour, minute, second)) = now();
+    let path = format!(
+        "{}/crash-{:04}-{:02}-{:02}-{:02}{:02}{:02}.txt",
+        CRASH_DIR, year, month, day, hour, minute, second
+    );
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Barista crash report");
+    let _ = writeln!(
+        report,
+        "Version: {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_HASH").trim()
+    );
+    let features: Vec<_> = FEATURES.iter().filter(|c| c.1).map(|c| c.0).collect();
+    let _ = writeln!(
+        report,
+        "Features: {}",
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        }
+    );
+    let _ = writeln!(
+        report,
+        "Time: {:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    );
+
+    let _ = writeln!(report, "\n{}", message);
+    match location {
+        Some(c) => {
+            let _ = writeln!(report, "at {}:{}:{}", c.file(), c.line(), c.column());
+            let _ = writeln!(report, "\nBacktrace:\n{}", backtrace());
+        }
+        None => {
+            let _ = writeln!(report, "\nNo backtrace, this was an error and not a panic");
+        }
+    }
+
+    let context = match CONTEXT.try_lock() {
+        Ok(c) => Some(c),
+        Err(TryLockError::Poisoned(c)) => Some(c.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    };
+    if let Some(context) = context {
+        let _ = writeln!(report, "\nGames:");
+        if context.games.is_empty() {
+            let _ = writeln!(report, "  (none found)");
+        }
+        for c in &context.games {
+            let _ = writeln!(report, "  {}", c);
+        }
+        let _ = writeln!(report, "\nSettings:");
+        let _ = writeln!(
+            report,
+            "{}",
+            context.settings.as_deref().unwrap_or("(not loaded yet)")
+        );
+        let _ = writeln!(report, "Mod slots:");
+        if context.mod_slots.is_empty() {
+            let _ = writeln!(report, "  (none)");
+        }
+        for c in &context.mod_slots {
+            let _ = writeln!(report, "  {}", c);
+        }
+    } else {
+        let _ = writeln!(
+            report,
+            "\n(No games, settings or mod slots, they were being updated)"
+        );
+    }
+
+    fs::create_dir_all(CRASH_DIR)?;
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// One address per frame. Looking up symbols in the middle of a panic is asking for a second
+/// one, so they're left for `arm-none-eabi-addr2line -fe barista.elf` to find afterwards
+fn backtrace() -> String {
+    let backtrace = Backtrace::new_unresolved();
+    let mut out = String::new();
+    for (i, frame) in backtrace.frames().iter().enumerate() {
+        let _ = writeln!(out, "  {:>2}: {:#010X}", i, frame.ip() as usize);
+    }
+    out
+}
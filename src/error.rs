@@ -6,6 +6,12 @@ use std::{
 };
 use toml::{de::Error as TomlDeError, ser::Error as TomlSeError};
 
+#[cfg(feature = "audio")]
+use crate::format::bcstm::BcstmProblem;
+use crate::{
+    app::SETTINGS_PATH, launcher::LaunchOp, plgldr::Error as PlgLdrError, saltwater::InstallOp,
+};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -14,22 +20,147 @@ pub enum Error {
     Io(IoError),
     TomlDe(TomlDeError),
     TomlSer(TomlSeError),
+    /// A BCSTM that can't be played
+    #[cfg(feature = "audio")]
+    AudioFormat {
+        path: String,
+        problem: BcstmProblem,
+    },
+    /// Every NDSP channel is taken by other streams
+    #[cfg(feature = "audio")]
+    NoAudioChannels,
+    /// saltwater.cfg or a mod profile couldn't be read or written
+    SaltwaterConfig {
+        op: FileOp,
+        path: String,
+        source: IoError,
+    },
+    /// The mods folder couldn't be looked through
+    ModScan {
+        op: FileOp,
+        path: String,
+        source: IoError,
+    },
+    /// Something that gets moved around in Luma's folders for launching couldn't be
+    Launcher {
+        op: LaunchOp,
+        from: String,
+        to: String,
+        source: IoError,
+    },
+    PluginLoader(PlgLdrError),
+    /// A .3gx without a header Barista can read
+    NotPlugin(String),
+    /// Saltwater couldn't be updated or rolled back
+    SaltwaterInstall {
+        op: InstallOp,
+        path: String,
+        source: IoError,
+    },
+    /// Rolling back without a previous Saltwater to go back to
+    NoSaltwaterBackup,
     Other(String),
 }
 
+/// What was being done with a file when it went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOp {
+    Read,
+    Write,
+    List,
+    CreateDir,
+}
+
+impl Display for FileOp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> FmtResult {
+        fmt.write_str(match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::List => "look through",
+            Self::CreateDir => "create",
+        })
+    }
+}
+
 impl Display for self::Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> FmtResult {
-        write!(
-            fmt,
-            "{}",
-            match self {
-                Self::Io(c) => c.to_string(),
-                Self::Ctru(c) => c.to_string(),
-                Self::Other(c) => c.to_string(),
-                Self::TomlDe(c) => c.to_string(),
-                Self::TomlSer(c) => c.to_string(),
+        match self {
+            Self::Ctru(CtruError::Os(c)) => write!(fmt, "System error {:#X}", c),
+            Self::Ctru(CtruError::Libc(c)) => write!(fmt, "libc error:\n{}", c),
+            Self::Ctru(CtruError::ServiceAlreadyActive) => write!(fmt, "Service already active"),
+            Self::Ctru(CtruError::OutputAlreadyRedirected) => {
+                write!(fmt, "Output already redirected")
+            }
+            Self::Ctru(c) => write!(fmt, "Unknown ctru error\n{}", c),
+            Self::Io(c) => write!(fmt, "IO error: {}", c),
+            Self::TomlDe(c) => write!(fmt, "TOML deserialize error: {}", c),
+            Self::TomlSer(c) => write!(fmt, "TOML serialize error: {}", c),
+            #[cfg(feature = "audio")]
+            Self::AudioFormat { path, problem } => write!(fmt, "Can't play {}:\n{}", path, problem),
+            #[cfg(feature = "audio")]
+            Self::NoAudioChannels => write!(fmt, "No NDSP channels available"),
+            Self::SaltwaterConfig { op, path, source } | Self::ModScan { op, path, source } => {
+                write!(fmt, "Couldn't {} {}:\n{}", op, path, source)
             }
-        )
+            Self::Launcher {
+                op,
+                from,
+                to,
+                source,
+            } => write!(fmt, "Couldn't {}\n({} -> {}):\n{}", op, from, to, source),
+            Self::PluginLoader(c) => write!(fmt, "{}", c),
+            Self::NotPlugin(c) => write!(fmt, "{} isn't a 3GX plugin", c),
+            Self::SaltwaterInstall { op, path, source } => {
+                write!(fmt, "Couldn't {}\n({}):\n{}", op, path, source)
+            }
+            Self::NoSaltwaterBackup => write!(fmt, "There's no previous Saltwater to go back to"),
+            Self::Other(c) => write!(fmt, "{}", c),
+        }
+    }
+}
+
+impl self::Error {
+    /// What the user can do about it, for the error screen
+    pub fn hint(&self) -> Option<String> {
+        Some(match self {
+            Self::TomlDe(_) => tr!("error.settings", SETTINGS_PATH),
+            // Running out of file means it's cut short, which is the file's fault
+            #[cfg(feature = "audio")]
+            Self::AudioFormat {
+                path,
+                problem: BcstmProblem::Read(c),
+            } if c.kind() != std::io::ErrorKind::UnexpectedEof => tr!("error.audio_read", path),
+            #[cfg(feature = "audio")]
+            Self::AudioFormat { path, .. } => tr!("error.audio_format", path),
+            #[cfg(feature = "audio")]
+            Self::NoAudioChannels => tr!("error.audio_channels"),
+            Self::SaltwaterConfig {
+                op: FileOp::Read,
+                path,
+                ..
+            } => tr!("error.config_read", path),
+            Self::SaltwaterConfig { .. } | Self::ModScan { .. } => tr!("error.sd_card"),
+            Self::Launcher { from, .. } => tr!("error.launch_files", from),
+            Self::SaltwaterInstall { path, .. } => tr!("error.launch_files", path),
+            Self::NotPlugin(c) => tr!("error.not_plugin", c),
+            Self::PluginLoader(PlgLdrError::Unavailable(_)) => tr!("error.plgldr_missing"),
+            Self::PluginLoader(PlgLdrError::Request(_)) => tr!("error.plgldr_request"),
+            Self::PluginLoader(PlgLdrError::PathTooLong { path, .. }) => tr!("error.path", path),
+            Self::PluginLoader(PlgLdrError::PathHasNul(path)) => tr!("error.path_nul", path),
+            Self::Ctru(_)
+            | Self::Io(_)
+            | Self::TomlSer(_)
+            | Self::NoSaltwaterBackup
+            | Self::Other(_) => return None,
+        })
+    }
+
+    /// The error and its hint, if there's one, for showing to the user
+    pub fn with_hint(&self) -> String {
+        match self.hint() {
+            Some(c) => format!("{}\n\n{}", self, c),
+            None => self.to_string(),
+        }
     }
 }
 
@@ -59,12 +190,19 @@ impl From<TomlSeError> for self::Error {
     }
 }
 
+impl From<PlgLdrError> for self::Error {
+    fn from(err: PlgLdrError) -> Self {
+        Self::PluginLoader(err)
+    }
+}
+
 pub fn error_applet(msg: String) {
     use ctru_sys::{
         aptExit, errorConf, errorDisp, errorInit, errorText, CFG_LANGUAGE_EN, ERROR_TEXT_WORD_WRAP,
     };
 
-    let msg = CString::new(msg).unwrap();
+    // CString can't hold NULs, and nothing stops a panic message from having one
+    let msg = CString::new(msg.replace('\0', "\\0")).expect("NULs were just replaced");
 
     unsafe {
         let mut error_conf: errorConf = errorConf::default();
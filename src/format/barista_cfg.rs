@@ -1,17 +1,18 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{Read, Write},
     path::PathBuf,
 };
 
-use crate::Result;
+use crate::{input::Bindings, launcher::GameVer, Result};
 use serde::{Deserialize, Serialize};
 
 pub fn r#true() -> bool {
     true
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct BaristaConfig {
     #[serde(skip, default = "bool::default")]
     pub is_new: bool,
@@ -22,9 +23,48 @@ pub struct BaristaConfig {
     pub btk_loaded_msg: bool,
     #[serde(default)]
     pub extra_rows: bool,
+    /// Have Saltwater put RHMPatch back after the game exits, if it was moved out of the way
+    #[serde(default = "r#true")]
+    pub restore_rhmpatch: bool,
+    /// Have Saltwater turn the plugin loader back off after the game exits, if it was off
+    #[serde(default = "r#true")]
+    pub restore_plgldr: bool,
+    /// Launch the last played version on startup, without opening the menu
+    #[serde(default)]
+    pub quick_launch: bool,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub bindings: Bindings,
+    /// Key of the version launched last time, see [`GameVer::key`]
+    #[serde(default)]
+    pub last_launched: Option<String>,
+    /// Launch options for each version, keyed by either [`GameVer::key`] (`US-digital`) or
+    /// just the region (`US`)
+    #[serde(default)]
+    pub versions: HashMap<String, LaunchOptions>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct LaunchOptions {
+    /// SD path of the Saltwater build to load, instead of the one in /spicerack/bin
+    pub plugin: Option<String>,
+    /// Mod profile to load, instead of the default one
+    pub profile: Option<String>,
+}
+
+/// The choice after `current`, going back to the default (None) after the last one. Anything
+/// that isn't one of `choices` goes back to the default too
+pub fn next_choice(choices: &[String], current: Option<&str>) -> Option<String> {
+    let next = match current {
+        None => 0,
+        Some(c) => choices.iter().position(|p| p == c)? + 1,
+    };
+    choices.get(next).cloned()
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum SlotTitleMode {
     Megamix,
     Original,
@@ -40,6 +80,13 @@ impl Default for BaristaConfig {
             slot_titles: Default::default(),
             btk_loaded_msg: true,
             extra_rows: false,
+            restore_rhmpatch: true,
+            restore_plgldr: true,
+            quick_launch: false,
+            language: None,
+            bindings: Bindings::default(),
+            last_launched: None,
+            versions: HashMap::new(),
         }
     }
 }
@@ -57,7 +104,18 @@ impl BaristaConfig {
             Ok(mut file) => {
                 let mut string = String::new();
                 file.read_to_string(&mut string)?;
-                Ok(toml::from_str(&string)?)
+                let mut config: Self = toml::from_str(&string)?;
+                if let Some(e) = config.bindings.problem() {
+                    log!(
+                        Warn,
+                        Config,
+                        "{}: {}, using the default bindings",
+                        path.display(),
+                        e
+                    );
+                    config.bindings = Bindings::default();
+                }
+                Ok(config)
             }
             Err(e) => {
                 if e.kind() == std::io::ErrorKind::NotFound {
@@ -74,6 +132,41 @@ impl BaristaConfig {
         }
     }
 
+    /// Options for launching `ver`. Options for the exact version win over the region's
+    pub fn launch_options(&self, ver: &GameVer) -> LaunchOptions {
+        let exact = self.versions.get(&ver.key());
+        let region = self.versions.get(ver.region.code());
+        LaunchOptions {
+            plugin: exact
+                .and_then(|c| c.plugin.clone())
+                .or_else(|| region.and_then(|c| c.plugin.clone())),
+            profile: exact
+                .and_then(|c| c.profile.clone())
+                .or_else(|| region.and_then(|c| c.profile.clone())),
+        }
+    }
+
+    /// Sets the options for the version `key`, forgetting it once they're back to the defaults
+    pub fn set_launch_options(&mut self, key: String, options: LaunchOptions) {
+        if options == LaunchOptions::default() {
+            self.versions.remove(&key);
+        } else {
+            self.versions.insert(key, options);
+        }
+    }
+
+    /// Every mod profile used by some version, sorted
+    pub fn profiles(&self) -> Vec<String> {
+        let mut out: Vec<_> = self
+            .versions
+            .values()
+            .filter_map(|c| c.profile.clone())
+            .collect();
+        out.sort();
+        out.dedup();
+        out
+    }
+
     pub fn to_file(&self, path: impl Into<PathBuf>) -> Result<()> {
         let mut f = File::create(path.into())?;
         f.write_all(toml::to_string_pretty(self)?.as_bytes())?;
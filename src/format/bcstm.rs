@@ -7,13 +7,16 @@ use ctru_sys::{
     NDSP_FORMAT_ADPCM, NDSP_WBUF_DONE,
 };
 use std::{
+    fmt::{self, Display},
     fs::File,
-    io::{Read, Seek, SeekFrom},
+    io::{self, Read, Seek, SeekFrom},
     mem::{self, MaybeUninit},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
 };
 
-static mut ACTIVE_NDSP_CHANNELS: u32 = 0;
+/// Bitmask of the NDSP channels taken by open streams
+static ACTIVE_NDSP_CHANNELS: AtomicU32 = AtomicU32::new(0);
 
 macro_rules! ninty_version {
     ($major:literal, $minor:literal, $patch:literal) => {
@@ -21,6 +24,43 @@ macro_rules! ninty_version {
     };
 }
 
+/// Why a BCSTM can't be played
+#[derive(Debug)]
+pub enum BcstmProblem {
+    /// The file ended early or couldn't be read at all
+    Read(io::Error),
+    NotBcstm,
+    InvalidBom,
+    UnsupportedRevision(u32),
+    NoDataSection,
+    NoInfoSection,
+    /// Only DSP ADPCM can be played
+    UnsupportedEncoding(u8),
+    TooManyChannels(usize),
+}
+
+impl Display for BcstmProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read(c) => write!(f, "{}", c),
+            Self::NotBcstm => write!(f, "Not a BCSTM file"),
+            Self::InvalidBom => write!(f, "Invalid BOM"),
+            Self::UnsupportedRevision(c) => write!(
+                f,
+                "Unsupported revision {:X} // {:X}",
+                ninty_version!(2, 3, 1),
+                c
+            ),
+            Self::NoDataSection => write!(f, "No data_offset section"),
+            Self::NoInfoSection => write!(f, "No info_offset section"),
+            Self::UnsupportedEncoding(c) => {
+                write!(f, "Encoding {} not supported (only DSP ADPCM supported)", c)
+            }
+            Self::TooManyChannels(c) => write!(f, "{} channels, only up to 2 are supported", c),
+        }
+    }
+}
+
 pub struct BCSTMFile {
     file: File,
 
@@ -53,6 +93,7 @@ impl Drop for BCSTMFile {
         unsafe {
             for i in 0..self.channel_count {
                 ndspChnWaveBufClear(self.channel[i] as i32);
+                ACTIVE_NDSP_CHANNELS.fetch_and(!(1 << self.channel[i]), Ordering::SeqCst);
             }
         }
     }
@@ -63,7 +104,28 @@ impl BCSTMFile {
 
     // public functions
     pub fn open_from_file(filename: impl Into<PathBuf>) -> Result<Self> {
-        let mut file = File::open(filename.into())?;
+        let path = filename.into();
+        Self::parse(&path).map_err(|e| match e {
+            Error::Io(c) => Error::AudioFormat {
+                path: path.display().to_string(),
+                problem: BcstmProblem::Read(c),
+            },
+            Error::AudioFormat { problem, .. } => Error::AudioFormat {
+                path: path.display().to_string(),
+                problem,
+            },
+            c => c,
+        })
+    }
+
+    /// Problems with the file come out as [`Error::AudioFormat`] with no path, which
+    /// [`Self::open_from_file`] fills in
+    fn parse(path: &Path) -> Result<Self> {
+        let invalid = |problem| Error::AudioFormat {
+            path: String::new(),
+            problem,
+        };
+        let mut file = File::open(path)?;
 
         // ************
         // *  HEADER  *
@@ -72,23 +134,19 @@ impl BCSTMFile {
         let mut magic_buf = [0u8; 4];
         file.read_exact(&mut magic_buf)?;
         if magic_buf != [b'C', b'S', b'T', b'M'] {
-            Err(Error::Other("BCSTM - Not a BCSTM file".to_string()))?;
+            Err(invalid(BcstmProblem::NotBcstm))?;
         }
 
         let endian = match u16::read_from(&mut file, ByteOrder::LittleEndian)? {
             0xFFFE => ByteOrder::BigEndian,
             0xFEFF => ByteOrder::LittleEndian,
-            _ => Err(Error::Other("BCSTM - Invalid BOM".to_string()))?,
+            _ => Err(invalid(BcstmProblem::InvalidBom))?,
         };
         u16::read_from(&mut file, endian)?; // Header size - 0x40
 
         let version = u32::read_from(&mut file, endian)?;
         if version != ninty_version!(2, 3, 1) {
-            Err(Error::Other(format!(
-                "BCSTM - unsupported revision {:X} // {:X}",
-                ninty_version!(2, 3, 1),
-                version
-            )))?
+            Err(invalid(BcstmProblem::UnsupportedRevision(version)))?
         }
 
         u32::read_from(&mut file, endian)?; // Complete filesize - unnecessary
@@ -113,12 +171,12 @@ impl BCSTMFile {
         let data_offset = if let Some(c) = data_offset {
             c
         } else {
-            Err(Error::Other("BCSTM: no data_offset section".to_string()))?
+            Err(invalid(BcstmProblem::NoDataSection))?
         };
         let info_offset = if let Some(c) = info_offset {
             c as u64
         } else {
-            Err(Error::Other("BCSTM: no info_offset section".to_string()))?
+            Err(invalid(BcstmProblem::NoInfoSection))?
         };
 
         // ****************
@@ -132,17 +190,13 @@ impl BCSTMFile {
 
         let encoding = u8::read_from(&mut file, endian)?;
         if encoding != 2 {
-            Err(Error::Other(
-                "BCSTM - encoding not supported (only DSP ADPCM supported)".to_string(),
-            ))?
+            Err(invalid(BcstmProblem::UnsupportedEncoding(encoding)))?
         }
 
         let looping = u8::read_from(&mut file, endian)? != 0;
         let channel_count = u8::read_from(&mut file, endian)? as usize;
         if channel_count > 2 {
-            Err(Error::Other(
-                "Unknown BCSTM error - channel_count".to_string(),
-            ))?
+            Err(invalid(BcstmProblem::TooManyChannels(channel_count)))?
         }
         u8::read_from(&mut file, endian)?;
 
@@ -236,14 +290,19 @@ impl BCSTMFile {
     // in the original code's play function
     unsafe fn init(&mut self) -> Result<()> {
         for i in 0..self.channel_count {
-            while ((ACTIVE_NDSP_CHANNELS >> self.channel[i]) & 1) == 1 {
-                self.channel[i] += 1;
-
+            // Claim the first free channel, retrying if another stream got to it first
+            loop {
                 if self.channel[i] >= 24 {
-                    Err(Error::Other("No NDSP channels available".to_string()))?
+                    // Only hand back the channels that were actually claimed on drop
+                    self.channel_count = i;
+                    Err(Error::NoAudioChannels)?
                 }
+                let bit = 1 << self.channel[i];
+                if ACTIVE_NDSP_CHANNELS.fetch_or(bit, Ordering::SeqCst) & bit == 0 {
+                    break;
+                }
+                self.channel[i] += 1;
             }
-            ACTIVE_NDSP_CHANNELS |= 1 << self.channel[i];
             ndspChnWaveBufClear(self.channel[i].into());
 
             let mut mix: [f32; 16] = [0.0; 16];
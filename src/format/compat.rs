@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+// Built into the executable, so it always matches the Saltwater version Barista ships with
+const TABLE: &str = include_str!("../../gfx/compat.toml");
+
+lazy_static! {
+    pub static ref COMPAT: CompatTable =
+        toml::from_str(TABLE).expect("Built-in compatibility table is broken");
+}
+
+/// Which game and update versions Saltwater supports, keyed by region code (`US`, `EU`...)
+#[derive(Deserialize, Debug)]
+#[serde(transparent)]
+pub struct CompatTable(HashMap<String, RegionCompat>);
+
+#[derive(Deserialize, Debug)]
+pub struct RegionCompat {
+    pub versions: Vec<u16>,
+    #[serde(default)]
+    pub updates: Vec<u16>,
+}
+
+impl CompatTable {
+    /// Whether Saltwater works with `version` of the game, with `update` installed if any
+    pub fn is_supported(&self, region: &str, version: u16, update: Option<u16>) -> bool {
+        let Some(c) = self.0.get(region) else {
+            return false;
+        };
+        c.versions.contains(&version) && update.map_or(true, |u| c.updates.contains(&u))
+    }
+}
@@ -2,4 +2,5 @@
 pub mod bcstm;
 
 pub mod barista_cfg;
+pub mod compat;
 pub mod saltwater_cfg;
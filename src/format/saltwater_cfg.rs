@@ -1,14 +1,14 @@
-use crate::Result;
+use crate::{error::FileOp, Error, Result};
 use bytestream::*;
 use std::{
     collections::HashMap,
     ffi::OsStr,
     fs::File,
     io::{self, Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct Config {
     pub btks: HashMap<u16, String>,
 }
@@ -17,11 +17,45 @@ const MAGIC: &[u8; 4] = b"SCF\x02";
 
 impl Config {
     pub fn from_file(file: impl Into<PathBuf>) -> Result<Self> {
-        let mut file = File::open(file.into())?;
+        let path = file.into();
+        Self::read(&path).map_err(|source| Error::SaltwaterConfig {
+            op: FileOp::Read,
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    /// Like [`Config::from_file`], but a file that isn't there yet is just an empty config
+    pub fn from_file_or_default(file: impl Into<PathBuf>) -> Result<Self> {
+        let path = file.into();
+        match Self::read(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            c => c.map_err(|source| Error::SaltwaterConfig {
+                op: FileOp::Read,
+                path: path.display().to_string(),
+                source,
+            }),
+        }
+    }
+
+    pub fn to_file(&self, file: impl Into<PathBuf>) -> Result<()> {
+        let path = file.into();
+        self.write(&path).map_err(|source| Error::SaltwaterConfig {
+            op: FileOp::Write,
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    fn read(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
         let mut magic_buffer = [0u8; 4];
         file.read_exact(&mut magic_buffer)?;
         if &magic_buffer != MAGIC {
-            Err(io::Error::new(io::ErrorKind::Other, "invalid file"))?;
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a saltwater.cfg file",
+            ))?;
         }
         let mut btks = HashMap::new();
         loop {
@@ -40,8 +74,8 @@ impl Config {
         Ok(Self { btks })
     }
 
-    pub fn to_file(&self, file: impl Into<PathBuf>) -> Result<()> {
-        let mut file = File::create(file.into())?;
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let mut file = File::create(path)?;
         file.write_all(MAGIC)?;
         for (index, string) in &self.btks {
             index.write_to(&mut file, ByteOrder::LittleEndian)?;
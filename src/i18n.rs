@@ -0,0 +1,185 @@
+use std::{fmt::Display, fs, sync::RwLock};
+
+use ctru::services::cfgu::{Cfgu, Language};
+use lazy_static::lazy_static;
+use toml::{Table, Value};
+
+use crate::{format::barista_cfg::BaristaConfig, Result};
+
+/// Languages with a string table in romfs:/text, and their display names
+pub const LANGUAGES: &[(&str, &str)] = &[("enUS", "English"), ("esES", "Español")];
+pub const DEFAULT_LANGUAGE: &str = "enUS";
+
+// Built into the executable so there's always something to fall back to
+const FALLBACK: &str = include_str!("../gfx/text/enUS/menu.toml");
+
+lazy_static! {
+    pub static ref TRANSLATION: RwLock<Translation> = RwLock::new(Translation::fallback());
+}
+
+pub struct Translation {
+    pub language: String,
+    table: Table,
+    fallback: Table,
+}
+
+impl Translation {
+    fn fallback() -> Self {
+        let table: Table = toml::from_str(FALLBACK).expect("Built-in enUS string table is broken");
+        Self {
+            language: DEFAULT_LANGUAGE.to_string(),
+            fallback: table.clone(),
+            table,
+        }
+    }
+
+    pub fn load(language: &str) -> Result<Self> {
+        let mut out = Self::fallback();
+        if language != DEFAULT_LANGUAGE {
+            let file = fs::read_to_string(format!("romfs:/text/{}/menu.toml", language))?;
+            out.table = toml::from_str(&file)?;
+            out.language = language.to_string();
+        }
+        Ok(out)
+    }
+
+    /// Looks up a `section.key` string, falling back to English and then to the key itself
+    pub fn get(&self, key: &str) -> String {
+        let text = lookup(&self.table, key)
+            .or_else(|| lookup(&self.fallback, key))
+            .unwrap_or(key);
+
+        // Multi-line strings are indented in the string tables to line up with the first line
+        text.lines()
+            .map(str::trim_start)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a str> {
+    let (section, key) = key.split_once('.')?;
+    match table.get(section)?.get(key)? {
+        Value::String(c) => Some(c),
+        _ => None,
+    }
+}
+
+pub fn system_language() -> &'static str {
+    let language = Cfgu::new().and_then(|c| c.language());
+    match language {
+        Ok(Language::Spanish) => "esES",
+        _ => DEFAULT_LANGUAGE,
+    }
+}
+
+/// Loads the language set in the Barista settings, or the console's if there's none. If its
+/// string table is missing or broken, English is used instead
+pub fn set_language(settings: &BaristaConfig) {
+    let language = match &settings.language {
+        Some(c) if LANGUAGES.iter().any(|(code, _)| code == c) => c.as_str(),
+        _ => system_language(),
+    };
+    let translation = match Translation::load(language) {
+        Ok(c) => c,
+        Err(e) => {
+            log!(
+                Warn,
+                General,
+                "Couldn't load the {} string table, using English: {}",
+                language,
+                e
+            );
+            Translation::fallback()
+        }
+    };
+    *TRANSLATION.write().unwrap() = translation;
+}
+
+pub fn tr(key: &str) -> String {
+    TRANSLATION.read().unwrap().get(key)
+}
+
+/// Like [`tr`], replacing every `{}` in the string with the next argument
+pub fn tr_fmt(key: &str, args: &[&dyn Display]) -> String {
+    let string = tr(key);
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = string.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        out += part;
+        if parts.peek().is_some() {
+            match args.next() {
+                Some(c) => out += &c.to_string(),
+                None => out += "{}",
+            }
+        }
+    }
+    out
+}
+
+#[macro_export]
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal $(, $i:expr)+ $(,)?) => {
+        $crate::i18n::tr_fmt($key, &[$(&$i),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, path::Path};
+
+    use super::*;
+
+    fn keys(path: &Path) -> BTreeSet<String> {
+        let file =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("Missing {}", path.display()));
+        let table: Table =
+            toml::from_str(&file).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+        let mut keys = BTreeSet::new();
+        for (section, values) in table {
+            if let Value::Table(values) = values {
+                keys.extend(values.keys().map(|c| format!("{}.{}", section, c)));
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn locales_match_english() {
+        let text = Path::new(env!("CARGO_MANIFEST_DIR")).join("gfx/text");
+        let base = keys(&text.join(DEFAULT_LANGUAGE).join("menu.toml"));
+
+        for dir in fs::read_dir(&text).unwrap() {
+            let dir = dir.unwrap().path();
+            if !dir.is_dir() {
+                continue;
+            }
+            let path = dir.join("menu.toml");
+            let keys = keys(&path);
+
+            let missing: Vec<_> = base.difference(&keys).collect();
+            let extra: Vec<_> = keys.difference(&base).collect();
+            assert!(
+                missing.is_empty() && extra.is_empty(),
+                "{} doesn't match {}\nmissing: {:?}\nunknown: {:?}",
+                path.display(),
+                DEFAULT_LANGUAGE,
+                missing,
+                extra
+            );
+        }
+    }
+
+    #[test]
+    fn every_language_has_a_table() {
+        let text = Path::new(env!("CARGO_MANIFEST_DIR")).join("gfx/text");
+        for (code, _) in LANGUAGES {
+            assert!(text.join(code).join("menu.toml").is_file(), "{}", code);
+        }
+    }
+}
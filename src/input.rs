@@ -0,0 +1,11 @@
+// Buttons and bindings live in barista_input so they can be tested on the host. This is where
+// they meet the real HID
+
+use ctru::services::hid::KeyPad;
+
+pub use input_lib::{Binding, Bindings, Button, Keys};
+
+/// `KeyPad` and [`Keys`] share the HID bit layout, so this is just a change of type
+pub fn keys(keypad: KeyPad) -> Keys {
+    Keys::from_bits(keypad.bits())
+}
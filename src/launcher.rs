@@ -1,17 +1,20 @@
 use crate::{
-    format::barista_cfg::BaristaConfig,
-    plgldr::{self, SaltwaterParams},
+    am::{Am, Media, TitleDb},
+    app::{config_path, CONFIG_PATH},
+    format::{barista_cfg::BaristaConfig, compat::COMPAT, saltwater_cfg::Config},
+    plgldr::{PluginLoader, SaltwaterParams},
+    version::Version,
+    Error, Result,
 };
 use libc::c_void;
 use std::{
-    ffi::CString,
     fmt::{self, Display},
     fs::{self, File},
 };
 
-use ctru_sys::{
-    amExit, amInit, svcExitProcess, AM_GetTitleInfo, MEDIATYPE_GAME_CARD, MEDIATYPE_SD,
-};
+use ctru_sys::svcExitProcess;
+
+pub const DEFAULT_PLUGIN: &str = "/spicerack/bin/Saltwater.3gx";
 
 const TITLE_JP: u64 = 0x0004000000155A00;
 const TITLE_US: u64 = 0x000400000018a400;
@@ -28,6 +31,39 @@ const TITLES: [GameRegion; 4] = [
 pub struct GameVer {
     pub region: GameRegion,
     pub is_digital: bool,
+    /// Title version of the game itself
+    pub version: u16,
+    /// Title version of the update, if there's one installed
+    pub update: Option<u16>,
+}
+
+impl GameVer {
+    /// Name for this version in cfg.toml, e.g. `US-digital`
+    pub fn key(&self) -> String {
+        format!(
+            "{}-{}",
+            self.region.code(),
+            if self.is_digital {
+                "digital"
+            } else {
+                "physical"
+            }
+        )
+    }
+
+    /// Whether Saltwater supports this game version and update, according to gfx/compat.toml
+    pub fn is_supported(&self) -> bool {
+        COMPAT.is_supported(self.region.code(), self.version, self.update)
+    }
+
+    /// Installed game and update versions, to show along with the version
+    pub fn version_info(&self) -> String {
+        let update = match self.update {
+            Some(c) => tr!("launcher.update", c),
+            None => tr!("launcher.no_update"),
+        };
+        format!("{} - {}", tr!("launcher.version", self.version), update)
+    }
 }
 
 impl Display for GameVer {
@@ -37,15 +73,15 @@ impl Display for GameVer {
             "{} ({})",
             self.region,
             if self.is_digital {
-                "Digital"
+                tr!("launcher.digital")
             } else {
-                "Physical"
+                tr!("launcher.physical")
             }
         )
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GameRegion {
     JP,
     US,
@@ -69,6 +105,15 @@ impl Display for GameRegion {
 }
 
 impl GameRegion {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::JP => "JP",
+            Self::US => "US",
+            Self::EU => "EU",
+            Self::KR => "KR",
+        }
+    }
+
     pub fn id(&self) -> u32 {
         match self {
             Self::JP => TITLE_JP as u32,
@@ -77,6 +122,11 @@ impl GameRegion {
             Self::KR => TITLE_KR as u32,
         }
     }
+    /// Title ID of the game's update, e.g. 0004000E0018A400
+    pub fn update_id(&self) -> u64 {
+        self.id_long() | 0x0000_000E_0000_0000
+    }
+
     pub fn id_long(&self) -> u64 {
         match self {
             Self::JP => TITLE_JP,
@@ -87,117 +137,478 @@ impl GameRegion {
     }
 }
 
-pub fn get_available_games() -> Vec<GameVer> {
+pub fn get_available_games() -> Result<Vec<GameVer>> {
+    find_games(&Am::new()?)
+}
+
+/// Every supported region of the game installed on the SD card or inserted as a cartridge
+pub fn find_games(titles: &impl TitleDb) -> Result<Vec<GameVer>> {
     let mut available_games = vec![];
-    //TODO: ctru-rs AM, whenever they add GetTitleInfo
-    unsafe {
-        assert!(amInit() == 0);
-        let mut null = std::mem::zeroed();
+    // Updates always go on the SD card, even for cartridges
+    let sd_titles = titles.list(Media::Sd)?;
+    let update = |title: &GameRegion| {
+        sd_titles
+            .contains(&title.update_id())
+            .then(|| titles.info(Media::Sd, title.update_id()))
+            .flatten()
+            .map(|c| c.version)
+    };
 
+    for (media, is_digital) in [(Media::Sd, true), (Media::GameCard, false)] {
+        let installed = match media {
+            Media::Sd => sd_titles.clone(),
+            // No cartridge inserted
+            Media::GameCard => titles.list(media).unwrap_or_default(),
+        };
         for title in &TITLES {
-            let id: *mut u64 = &mut title.id_long();
-            if AM_GetTitleInfo(MEDIATYPE_SD, 1, id, &mut null) == 0 {
-                match title {
-                    GameRegion::JP => {
-                        if cfg!(feature = "jp") {
-                            available_games.push(GameVer {
-                                region: GameRegion::JP,
-                                is_digital: true,
-                            })
-                        }
-                    }
-                    GameRegion::US => available_games.push(GameVer {
-                        region: GameRegion::US,
-                        is_digital: true,
-                    }),
-                    GameRegion::EU => available_games.push(GameVer {
-                        region: GameRegion::EU,
-                        is_digital: true,
-                    }),
-                    GameRegion::KR => available_games.push(GameVer {
-                        region: GameRegion::KR,
-                        is_digital: true,
-                    }),
+            if *title == GameRegion::JP && !cfg!(feature = "jp") {
+                continue;
+            }
+            if !installed.contains(&title.id_long()) {
+                continue;
+            }
+            let Some(info) = titles.info(media, title.id_long()) else {
+                continue;
+            };
+            let ver = GameVer {
+                region: title.clone(),
+                is_digital,
+                version: info.version,
+                update: update(title),
+            };
+            if !ver.is_supported() {
+                log!(
+                    Warn,
+                    Launcher,
+                    "Unsupported game version: {} {}",
+                    ver,
+                    ver.version_info()
+                );
+            }
+            available_games.push(ver);
+        }
+    }
+    Ok(available_games)
+}
+
+/// Oldest Luma3DS with the plugin loader built in
+pub const MIN_LUMA: Version = Version::new(13, 0, 0);
+
+/// Why Saltwater can't be loaded on this console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LumaProblem {
+    /// Some other CFW, or none at all
+    NotLuma,
+    /// Luma3DS older than [`MIN_LUMA`]
+    TooOld(Version),
+    /// Recent enough Luma3DS, but the plg:ldr service isn't there
+    NoPluginLoader,
+}
+
+/// Version of the Luma3DS running, or None if it's something else
+pub fn luma_version() -> Option<Version> {
+    let mut version = 0i64;
+    // Luma's own svcGetSystemInfo type
+    let result = unsafe { ctru_sys::svcGetSystemInfo(&mut version, 0x10000, 0) };
+    (result == 0).then_some(Version::from_packed(version as u32))
+}
+
+/// Makes sure the plugin loader is there to load Saltwater with. Citra has one without
+/// being Luma, so only the plugin loader itself is checked there
+pub fn check_luma(is_citra: bool) -> Option<LumaProblem> {
+    if !is_citra {
+        match luma_version() {
+            None => return Some(LumaProblem::NotLuma),
+            Some(c) if c < MIN_LUMA => return Some(LumaProblem::TooOld(c)),
+            Some(_) => {}
+        }
+    }
+
+    let loader = match PluginLoader::new() {
+        Ok(c) => c,
+        Err(e) => {
+            log!(Error, PlgLdr, "{}", e);
+            return Some(LumaProblem::NoPluginLoader);
+        }
+    };
+    // Being off is fine, it gets turned on when launching and the Run menu says so
+    match loader.is_enabled() {
+        Ok(enabled) => {
+            log!(
+                Info,
+                PlgLdr,
+                "Plugin loader is {}",
+                if enabled { "on" } else { "off" }
+            );
+            None
+        }
+        Err(e) => {
+            log!(Error, PlgLdr, "{}", e);
+            Some(LumaProblem::NoPluginLoader)
+        }
+    }
+}
+
+/// RHMPatch's code.ips for `region`, and where it's moved to while Saltwater runs
+fn rhmpatch_paths(region: &GameRegion) -> (String, String) {
+    let dir = format!("sdmc:/luma/titles/{:016X}", region.id_long());
+    (format!("{}/code.ips", dir), format!("{}/code.old.ips", dir))
+}
+
+pub fn check_for_rhmpatch(region: &GameRegion) -> bool {
+    File::open(rhmpatch_paths(region).0).is_ok()
+}
+
+/// Puts back RHMPatch for any region where a launch left it moved out of the way, which
+/// happens if the game crashes or the console is turned off before Saltwater restores it
+pub fn restore_stranded_rhmpatch() -> Result<()> {
+    for region in &TITLES {
+        let (patch, moved) = rhmpatch_paths(region);
+        if File::open(&moved).is_err() {
+            continue;
+        }
+        if File::open(&patch).is_ok() {
+            // Reinstalled since, the one in place wins
+            log!(
+                Info,
+                Launcher,
+                "{} was reinstalled, leaving code.old.ips be",
+                patch
+            );
+            continue;
+        }
+        fs::rename(&moved, &patch).map_err(|source| Error::Launcher {
+            op: LaunchOp::RestoreRhmPatch(region.clone()),
+            from: moved.clone(),
+            to: patch.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// What was being moved around in Luma's folders, for [`Error::Launcher`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchOp {
+    DisableRhmPatch(GameRegion),
+    RestoreRhmPatch(GameRegion),
+    /// With the conflict's label
+    DisableConflict(String),
+    RestoreConflict,
+    /// With the profile's name
+    UseProfile(String),
+    RestoreDefaultProfile,
+}
+
+impl Display for LaunchOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DisableRhmPatch(c) => write!(f, "disable RHMPatch for {}", c),
+            Self::RestoreRhmPatch(c) => write!(f, "restore RHMPatch for {}", c),
+            Self::DisableConflict(c) => write!(f, "disable {}", c),
+            Self::RestoreConflict => write!(f, "restore a disabled conflict"),
+            Self::UseProfile(c) => write!(f, "switch Saltwater to the {} profile", c),
+            Self::RestoreDefaultProfile => write!(f, "restore the default mod profile"),
+        }
+    }
+}
+
+/// Something in Luma's folders that changes the game under Saltwater
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub region: GameRegion,
+    pub kind: ConflictKind,
+    pub path: String,
+    /// Whether to move it out of the way when launching. Stays that way until Barista is
+    /// opened again
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// A romfs folder for LayeredFS
+    LayeredFs,
+    /// A replacement code.bin
+    CodeBin,
+    /// A replacement exheader.bin
+    Exheader,
+    /// A .3gx plugin Luma would load by default
+    Plugin,
+}
+
+// RHMPatch uses code.old.ips, anything else gets this added to its name while disabled
+const DISABLED_SUFFIX: &str = ".barista-off";
+
+fn title_dirs(region: &GameRegion) -> [String; 2] {
+    [
+        format!("sdmc:/luma/titles/{:016X}", region.id_long()),
+        format!("sdmc:/luma/plugins/{:016X}", region.id_long()),
+    ]
+}
+
+impl Conflict {
+    pub fn label(&self) -> String {
+        let kind = match self.kind {
+            ConflictKind::LayeredFs => tr!("conflicts.layeredfs"),
+            ConflictKind::CodeBin => "code.bin".to_string(),
+            ConflictKind::Exheader => "exheader.bin".to_string(),
+            ConflictKind::Plugin => tr!(
+                "conflicts.plugin",
+                self.path.rsplit('/').next().unwrap_or(&self.path)
+            ),
+        };
+        let state = if self.disabled {
+            tr!("conflicts.disabled")
+        } else {
+            tr!("conflicts.enabled")
+        };
+        format!("[{}] {}: {}", self.region.code(), kind, state)
+    }
+}
+
+/// Lists everything in Luma's title and plugin folders for the regions in `versions`, other
+/// than RHMPatch which is handled on its own
+pub fn find_conflicts(versions: &[GameVer]) -> Vec<Conflict> {
+    let mut out = vec![];
+    let mut regions: Vec<&GameRegion> = vec![];
+    for ver in versions {
+        if !regions.contains(&&ver.region) {
+            regions.push(&ver.region);
+        }
+    }
+
+    for region in regions {
+        let [titles, plugins] = title_dirs(region);
+        let mut found = |kind, path: String| {
+            out.push(Conflict {
+                region: region.clone(),
+                kind,
+                path,
+                disabled: false,
+            })
+        };
+
+        if fs::metadata(format!("{}/romfs", titles)).is_ok_and(|c| c.is_dir()) {
+            found(ConflictKind::LayeredFs, format!("{}/romfs", titles));
+        }
+        if File::open(format!("{}/code.bin", titles)).is_ok() {
+            found(ConflictKind::CodeBin, format!("{}/code.bin", titles));
+        }
+        if File::open(format!("{}/exheader.bin", titles)).is_ok() {
+            found(ConflictKind::Exheader, format!("{}/exheader.bin", titles));
+        }
+        if let Ok(dir) = fs::read_dir(&plugins) {
+            for entry in dir.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|c| c == "3gx") {
+                    found(ConflictKind::Plugin, path.to_string_lossy().into_owned());
                 }
             }
         }
+    }
+    out
+}
 
-        for title in &TITLES {
-            let id: *mut u64 = &mut title.id_long();
-            if AM_GetTitleInfo(MEDIATYPE_GAME_CARD, 1, id, &mut null) == 0 {
-                match title {
-                    GameRegion::JP => {
-                        if cfg!(feature = "jp") {
-                            available_games.push(GameVer {
-                                region: GameRegion::JP,
-                                is_digital: false,
-                            })
-                        }
-                    }
-                    GameRegion::US => available_games.push(GameVer {
-                        region: GameRegion::US,
-                        is_digital: false, //this will never happen
-                    }),
-                    GameRegion::EU => available_games.push(GameVer {
-                        region: GameRegion::EU,
-                        is_digital: false,
-                    }),
-                    GameRegion::KR => available_games.push(GameVer {
-                        region: GameRegion::KR,
-                        is_digital: false,
-                    }),
+/// Moves the conflicts marked as disabled for `region` out of the way
+pub fn disable_conflicts(conflicts: &[Conflict], region: &GameRegion) -> Result<()> {
+    for c in conflicts
+        .iter()
+        .filter(|c| c.disabled && &c.region == region)
+    {
+        let moved = format!("{}{}", c.path, DISABLED_SUFFIX);
+        fs::rename(&c.path, &moved).map_err(|source| Error::Launcher {
+            op: LaunchOp::DisableConflict(c.label()),
+            from: c.path.clone(),
+            to: moved.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+/// Puts back everything disabled by [`disable_conflicts`] in an earlier launch
+pub fn restore_disabled_conflicts() -> Result<()> {
+    for region in &TITLES {
+        for dir in title_dirs(region) {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let moved = entry.path().to_string_lossy().into_owned();
+                let Some(path) = moved.strip_suffix(DISABLED_SUFFIX) else {
+                    continue;
+                };
+                if fs::metadata(path).is_ok() {
+                    // Reinstalled since, the one in place wins
+                    continue;
                 }
+                fs::rename(&moved, path).map_err(|source| Error::Launcher {
+                    op: LaunchOp::RestoreConflict,
+                    from: moved.clone(),
+                    to: path.to_string(),
+                    source,
+                })?;
             }
         }
-        amExit();
     }
-    available_games
+    Ok(())
+}
+
+/// Where saltwater.cfg is kept while a mod profile takes its place
+fn default_profile_stash() -> String {
+    format!("{}{}", CONFIG_PATH, DISABLED_SUFFIX)
 }
 
-pub fn check_for_plgldr() {
-    let result = plgldr::init();
-    plgldr::exit();
-    match result {
-        Ok(_) => (),
-        Err(_) => panic!("Saltwater/Barista require Luma3DS v13.0+"), //TODO: proper error screen, install it..?
+/// Puts the mod slots of `profile` where Saltwater reads them, since it only ever reads
+/// saltwater.cfg. The default profile's saltwater.cfg is kept aside until
+/// [`restore_default_profile`]
+pub fn use_profile(profile: &str) -> Result<()> {
+    let stash = default_profile_stash();
+    // Still aside if putting it back failed last time, what's in place is another profile
+    if fs::metadata(&stash).is_err() {
+        if fs::metadata(CONFIG_PATH).is_ok() {
+            fs::rename(CONFIG_PATH, &stash).map_err(|source| Error::Launcher {
+                op: LaunchOp::UseProfile(profile.to_string()),
+                from: CONFIG_PATH.to_string(),
+                to: stash.clone(),
+                source,
+            })?;
+        } else {
+            // No mod slots to put back, but there has to be something to say it was swapped
+            Config::default().to_file(&stash)?;
+        }
     }
+    Config::from_file_or_default(config_path(Some(profile)))?.to_file(CONFIG_PATH)
 }
 
-pub fn check_for_rhmpatch() -> bool {
-    File::open("sdmc:/luma/titles/000400000018A400/code.ips").is_ok()
+/// Puts back the default profile's saltwater.cfg after [`use_profile`]
+pub fn restore_default_profile() -> Result<()> {
+    let stash = default_profile_stash();
+    if fs::metadata(&stash).is_err() {
+        return Ok(());
+    }
+    let failed = |source| Error::Launcher {
+        op: LaunchOp::RestoreDefaultProfile,
+        from: stash.clone(),
+        to: CONFIG_PATH.to_string(),
+        source,
+    };
+    // What's in place is only a copy of the profile, and renaming won't replace it
+    if fs::metadata(CONFIG_PATH).is_ok() {
+        fs::remove_file(CONFIG_PATH).map_err(failed)?;
+    }
+    fs::rename(&stash, CONFIG_PATH).map_err(failed)
 }
 
-pub fn launch(ver: GameVer, is_citra: bool, settings: &BaristaConfig) {
-    plgldr::init().unwrap();
+pub fn launch(ver: GameVer, is_citra: bool, settings: &BaristaConfig) -> Result<()> {
+    let loader = PluginLoader::new()?;
     let mut params = SaltwaterParams::default();
 
-    // disable rhmpatch if it exists
-    if check_for_rhmpatch() {
-        params.reenable_rhmpatch = true;
-        fs::rename(
-            "sdmc:/luma/titles/000400000018A400/code.ips",
-            "sdmc:/luma/titles/000400000018A400/code.old.ips",
-        )
-        .unwrap()
+    let options = settings.launch_options(&ver);
+    if let Some(c) = &options.profile {
+        if let Err(e) = use_profile(c) {
+            let _ = restore_default_profile();
+            return Err(e);
+        }
     }
 
     // enable plugin loader if it's not
-    if !plgldr::is_enabled().unwrap() {
-        params.disable_plgldr = true;
-        plgldr::set_state(true).unwrap();
+    let enabled_by_us = match loader.is_enabled() {
+        Ok(c) => !c,
+        Err(e) => {
+            let _ = restore_default_profile();
+            return Err(e.into());
+        }
+    };
+    if enabled_by_us {
+        if let Err(e) = loader.set_state(true) {
+            let _ = restore_default_profile();
+            return Err(e.into());
+        }
+    }
+
+    // disable rhmpatch if it exists
+    let rhmpatch_moved = check_for_rhmpatch(&ver.region);
+    let (patch, moved) = rhmpatch_paths(&ver.region);
+    if rhmpatch_moved {
+        if let Err(source) = fs::rename(&patch, &moved) {
+            if enabled_by_us {
+                let _ = loader.set_state(false);
+            }
+            let _ = restore_default_profile();
+            return Err(Error::Launcher {
+                op: LaunchOp::DisableRhmPatch(ver.region),
+                from: patch,
+                to: moved,
+                source,
+            });
+        }
+    }
+
+    params.apply_settings(settings, rhmpatch_moved, enabled_by_us);
+
+    let plugin = options.plugin.as_deref().unwrap_or(DEFAULT_PLUGIN);
+    log!(Info, Launcher, "Launching {} with {}", ver, plugin);
+    if let Err(e) = loader.set_params(true, ver.region.id(), plugin, params) {
+        log!(Error, PlgLdr, "Couldn't set the plugin to load: {}", e);
+        // The game isn't getting launched, so put everything back how it was
+        if rhmpatch_moved {
+            let _ = fs::rename(&moved, &patch);
+        }
+        if enabled_by_us {
+            let _ = loader.set_state(false);
+        }
+        let _ = restore_default_profile();
+        return Err(e.into());
+    }
+    drop(loader);
+
+    jump(&ver, is_citra);
+    Ok(())
+}
+
+/// Left behind when [`launch_vanilla`] turns the plugin loader off, so it's turned back on
+/// the next time Barista starts
+const PLGLDR_MARKER: &str = "sdmc:/spicerack/plgldr.barista-off";
+
+/// Launches `ver` without Saltwater or any other plugin. The plugin loader is turned off for
+/// it rather than sent empty parameters, since Luma takes a lowTitleId of 0 as any title. If
+/// it was on, [`restore_plugin_loader`] turns it back on the next time Barista starts.
+/// RHMPatch is left as it is, and disabled conflicts come back on that next start too, same
+/// as after a launch with Saltwater
+pub fn launch_vanilla(ver: GameVer, is_citra: bool) -> Result<()> {
+    log!(Info, Launcher, "Launching {} without Saltwater", ver);
+    let loader = PluginLoader::new()?;
+    if loader.is_enabled()? {
+        // Written first, a plugin loader left off with nothing saying so would stay that way
+        File::create(PLGLDR_MARKER)?;
+        if let Err(e) = loader.set_state(false) {
+            let _ = fs::remove_file(PLGLDR_MARKER);
+            return Err(e.into());
+        }
     }
+    drop(loader);
 
-    params.apply_settings(settings);
+    jump(&ver, is_citra);
+    Ok(())
+}
 
-    plgldr::set_params(
-        true,
-        ver.region.id(),
-        CString::new("/spicerack/bin/Saltwater.3gx").unwrap(),
-        params,
-    )
-    .unwrap();
-    plgldr::exit();
+/// Turns the plugin loader back on if [`launch_vanilla`] turned it off
+pub fn restore_plugin_loader() -> Result<()> {
+    if fs::metadata(PLGLDR_MARKER).is_err() {
+        return Ok(());
+    }
+    PluginLoader::new()?.set_state(true)?;
+    fs::remove_file(PLGLDR_MARKER)?;
+    log!(
+        Info,
+        PlgLdr,
+        "Turned the plugin loader back on after a vanilla launch"
+    );
+    Ok(())
+}
 
+fn jump(ver: &GameVer, is_citra: bool) {
     let mediatype = if ver.is_digital {
         ctru_sys::MEDIATYPE_SD
     } else {
@@ -222,3 +633,88 @@ pub fn launch(ver: GameVer, is_citra: bool, settings: &BaristaConfig) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::am::FakeTitleDb;
+
+    fn regions(games: &[GameVer]) -> Vec<(GameRegion, bool)> {
+        games
+            .iter()
+            .map(|c| (c.region.clone(), c.is_digital))
+            .collect()
+    }
+
+    #[test]
+    fn sd_before_cartridge() {
+        let titles = FakeTitleDb::new()
+            .install(Media::GameCard, TITLE_EU, 0)
+            .install(Media::Sd, TITLE_US, 0);
+        let games = find_games(&titles).unwrap();
+        assert_eq!(
+            regions(&games),
+            [(GameRegion::US, true), (GameRegion::EU, false)]
+        );
+    }
+
+    #[test]
+    fn same_region_on_both() {
+        let titles = FakeTitleDb::new().install(Media::Sd, TITLE_KR, 0).install(
+            Media::GameCard,
+            TITLE_KR,
+            0,
+        );
+        let games = find_games(&titles).unwrap();
+        assert_eq!(
+            regions(&games),
+            [(GameRegion::KR, true), (GameRegion::KR, false)]
+        );
+    }
+
+    #[test]
+    fn nothing_installed() {
+        assert!(find_games(&FakeTitleDb::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn updates_come_from_the_sd_card() {
+        let titles = FakeTitleDb::new()
+            .install(Media::Sd, TITLE_US, 0)
+            .install(Media::Sd, GameRegion::US.update_id(), 1024)
+            .install(Media::GameCard, TITLE_EU, 0)
+            .install(Media::Sd, GameRegion::EU.update_id(), 2048);
+        let games = find_games(&titles).unwrap();
+        assert_eq!(games[0].update, Some(1024));
+        assert_eq!(games[1].update, Some(2048));
+    }
+
+    #[test]
+    fn update_without_the_game_is_ignored() {
+        let titles = FakeTitleDb::new()
+            .install(Media::Sd, GameRegion::US.update_id(), 1024)
+            .install(Media::GameCard, TITLE_EU, 0)
+            // Nothing gets updated from a cartridge
+            .install(Media::GameCard, GameRegion::EU.update_id(), 2048);
+        let games = find_games(&titles).unwrap();
+        assert_eq!(regions(&games), [(GameRegion::EU, false)]);
+        assert_eq!(games[0].update, None);
+    }
+
+    #[test]
+    fn jp_only_with_the_feature() {
+        let titles =
+            FakeTitleDb::new()
+                .install(Media::Sd, TITLE_JP, 0)
+                .install(Media::Sd, TITLE_US, 0);
+        let games = find_games(&titles).unwrap();
+        if cfg!(feature = "jp") {
+            assert_eq!(
+                regions(&games),
+                [(GameRegion::JP, true), (GameRegion::US, true)]
+            );
+        } else {
+            assert_eq!(regions(&games), [(GameRegion::US, true)]);
+        }
+    }
+}
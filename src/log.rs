@@ -1,46 +1,303 @@
-use std::{fmt::Display, sync::Mutex};
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use lazy_static::lazy_static;
 
-#[cfg(debug_assertions)]
+pub const LOG_DIR: &str = "sdmc:/spicerack/logs";
+/// How many days of logs to keep around
+const MAX_FILES: usize = 7;
+/// How many lines are kept in memory for the log viewer and dumps, the file on the SD card
+/// has all of them
+const MAX_ENTRIES: usize = 1000;
+
+/// Anything less important than this is left out. Release builds keep warnings and errors
+/// so there's something to attach to bug reports
+pub const MAX_LEVEL: Level = if cfg!(debug_assertions) {
+    Level::Debug
+} else {
+    Level::Warn
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    const ALL: [Level; 4] = [Level::Error, Level::Warn, Level::Info, Level::Debug];
+
+    /// The next level with more (`more` = true) or less detail, staying within [`MAX_LEVEL`]
+    pub fn step(self, more: bool) -> Self {
+        let i = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        let i = if more {
+            (i + 1).min(Self::ALL.len() - 1)
+        } else {
+            i.saturating_sub(1)
+        };
+        Self::ALL[i].min(MAX_LEVEL)
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        })
+    }
+}
+
+/// What part of Barista a message comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(unused)]
 pub enum Log {
     General,
     Audio,
+    Launcher,
+    Config,
+    Ui,
+    PlgLdr,
+}
+
+impl Log {
+    pub const ALL: [Log; 6] = [
+        Log::General,
+        Log::Audio,
+        Log::Launcher,
+        Log::Config,
+        Log::Ui,
+        Log::PlgLdr,
+    ];
 }
 
-#[cfg(debug_assertions)]
 impl Display for Log {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(
-            match self {
-                Log::General => "general",
-                Log::Audio => "audio",
-            })
+        f.write_str(match self {
+            Log::General => "general",
+            Log::Audio => "audio",
+            Log::Launcher => "launcher",
+            Log::Config => "config",
+            Log::Ui => "ui",
+            Log::PlgLdr => "plgldr",
+        })
     }
 }
 
-#[cfg(debug_assertions)]
+pub struct Entry {
+    pub level: Level,
+    pub category: Log,
+    /// The line as written to the log file, without the newline
+    pub line: String,
+}
+
+/// Which log entries the log viewer shows
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Filter {
+    /// Least important level shown
+    pub level: Level,
+    /// None for every category
+    pub category: Option<Log>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            level: MAX_LEVEL,
+            category: None,
+        }
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        entry.level <= self.level && self.category.map_or(true, |c| c == entry.category)
+    }
+
+    /// Moves on to the next category, going back to all of them after the last one
+    pub fn next_category(&mut self) {
+        self.category = match self.category {
+            None => Some(Log::ALL[0]),
+            Some(c) => {
+                let i = Log::ALL.iter().position(|l| *l == c).unwrap_or(0);
+                Log::ALL.get(i + 1).copied()
+            }
+        }
+    }
+}
+
+pub struct Logger {
+    /// The last [`MAX_ENTRIES`] lines logged this session
+    pub entries: VecDeque<Entry>,
+    /// Today's log on the SD card, None until [`init`] or if it couldn't be opened
+    file: Option<File>,
+}
+
 lazy_static! {
-    pub static ref LOG: Mutex<String> = Mutex::new(String::new());
+    pub static ref LOG: Mutex<Logger> = Mutex::new(Logger {
+        entries: VecDeque::new(),
+        file: None,
+    });
 }
 
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! log {
-    ($type:ident, $lit:literal $(, $i:expr)* $(,)?) => {
-        let out = {
-            use $crate::log::Log::*;
-            format!("<{}> {}\n", $type.to_string(), format!($lit, $($i,)?))
-        };
+/// Starts writing the log to the SD card, including anything kept from before this, and
+/// deletes the oldest logs
+pub fn init() {
+    let (year, month, day) = now().0;
+    let path = format!(
+        "{}/barista-{:04}-{:02}-{:02}.log",
+        LOG_DIR, year, month, day
+    );
+
+    let file = fs::create_dir_all(LOG_DIR).and_then(|_| {
+        rotate(&path);
+        OpenOptions::new().create(true).append(true).open(&path)
+    });
+
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    match file {
+        Ok(mut file) => {
+            for c in &log.entries {
+                let _ = writeln!(file, "{}", c.line);
+            }
+            log.file = Some(file);
+        }
+        Err(e) => {
+            drop(log);
+            write(
+                Level::Warn,
+                Log::General,
+                &format!("Couldn't open {}: {}", path, e),
+            );
+        }
+    }
+}
+
+/// Lines in this session's log that pass `filter`
+pub fn lines(filter: &Filter) -> Vec<String> {
+    let Ok(log) = LOG.lock() else {
+        return vec![];
+    };
+    log.entries
+        .iter()
+        .filter(|c| filter.matches(c))
+        .map(|c| c.line.clone())
+        .collect()
+}
+
+/// Writes the lines kept from this session to a new file, returning its path
+pub fn dump() -> crate::Result<String> {
+    let ((year, month, day), (hour, minute, second)) = now();
+    let path = format!(
+        "{}/dump-{:04}-{:02}-{:02}-{:02}{:02}{:02}.log",
+        LOG_DIR, year, month, day, hour, minute, second
+    );
+    let contents = {
+        let log = LOG
+            .lock()
+            .map_err(|_| crate::Error::Other("The log is unavailable".to_string()))?;
+        log.entries
+            .iter()
+            .map(|c| c.line.clone() + "\n")
+            .collect::<String>()
+    };
+
+    fs::create_dir_all(LOG_DIR)?;
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// Deletes the oldest logs so there's room for `today`
+fn rotate(today: &str) {
+    let Ok(dir) = fs::read_dir(LOG_DIR) else {
+        return;
+    };
+    let mut logs: Vec<_> = dir
+        .flatten()
+        .map(|c| c.path())
+        .filter(|c| {
+            let name = c.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("barista-") && name.ends_with(".log")
+        })
+        .filter(|c| c.to_string_lossy() != today)
+        .collect();
+    // Dates sort the same as their names
+    logs.sort();
+
+    let extra = (logs.len() + 1).saturating_sub(MAX_FILES);
+    for c in &logs[..extra] {
+        let _ = fs::remove_file(c);
+    }
+}
+
+/// Adds a line to the log, use [`log!`] instead
+pub fn write(level: Level, category: Log, message: &str) {
+    let (hour, minute, second) = now().1;
+    let line = format!(
+        "[{:02}:{:02}:{:02}] {:<5} {}: {}",
+        hour, minute, second, level, category, message
+    );
 
-        let mut log = $crate::log::LOG.lock().unwrap();
-        *log += &out;
+    let Ok(mut log) = LOG.lock() else {
+        return;
     };
+    if let Some(file) = &mut log.file {
+        let _ = writeln!(file, "{}", line);
+    }
+    if log.entries.len() == MAX_ENTRIES {
+        log.entries.pop_front();
+    }
+    log.entries.push_back(Entry {
+        level,
+        category,
+        line,
+    });
 }
 
+/// Date and time on the console's clock
+pub fn now() -> ((i64, u32, u32), (u32, u32, u32)) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |c| c.as_secs() as i64);
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400) as u32);
+
+    // Days since 1970 to a date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    ((year, month, day), (secs / 3600, secs / 60 % 60, secs % 60))
+}
+
+/// Logs a message at a [`Level`] under a [`Log`] category, like
+/// `log!(Warn, Launcher, "Couldn't find {}", path)`
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! log {
-    ($type:ident, $lit:literal $(, $i:expr)* $(,)?) => {};
+    ($level:ident, $category:ident, $lit:literal $(, $i:expr)* $(,)?) => {
+        if $crate::log::Level::$level <= $crate::log::MAX_LEVEL {
+            $crate::log::write(
+                $crate::log::Level::$level,
+                $crate::log::Log::$category,
+                &format!($lit $(, $i)*),
+            )
+        }
+    };
 }
@@ -0,0 +1,43 @@
+// Help screen for when Saltwater can't be loaded, explaining how to get Luma3DS in order
+
+use ctru::services::{
+    apt::Apt,
+    gfx::Gfx,
+    hid::{Hid, KeyPad},
+};
+use ui_lib::BaristaUI;
+
+use crate::{
+    launcher::{LumaProblem, MIN_LUMA},
+    scene::menu::{backend::MenuRenderer, render::MenuView},
+};
+
+/// Shows how to fix `problem` until the user leaves, Barista can't do anything else anyway
+pub fn show(apt: &Apt, gfx: &Gfx, hid: &mut Hid, ui: &mut BaristaUI, problem: LumaProblem) {
+    let header = match problem {
+        LumaProblem::NotLuma => tr!("luma.not_luma", MIN_LUMA),
+        LumaProblem::TooOld(c) => tr!("luma.too_old", MIN_LUMA, c),
+        LumaProblem::NoPluginLoader => tr!("luma.no_plgldr"),
+    };
+    ui.show(&MenuView {
+        title: tr!("text_menu.title") + &tr!("luma.title"),
+        header,
+        footer: tr!("luma.exit"),
+        back_button: true,
+        ..Default::default()
+    });
+
+    while apt.main_loop() {
+        gfx.wait_for_vblank();
+        hid.scan_input();
+        ui.render();
+
+        // Bindings aren't loaded yet, so stick to the defaults
+        if hid
+            .keys_down()
+            .intersects(KeyPad::B | KeyPad::START | KeyPad::TOUCH)
+        {
+            break;
+        }
+    }
+}
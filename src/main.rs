@@ -1,5 +1,6 @@
 #![feature(allocator_api, int_roundings, panic_backtrace_config)]
 
+extern crate barista_input as input_lib;
 extern crate barista_ui as ui_lib;
 
 use ctru::{
@@ -8,14 +9,12 @@ use ctru::{
 };
 use error::error_applet;
 use std::{
-    panic::{self, PanicHookInfo},
-    process,
+    panic::{self, Location, PanicHookInfo},
+    process, ptr,
     time::Duration,
 };
 use ui_lib::{BaristaUI, Screen};
 
-mod error;
-
 use self::error::{Error, Result};
 
 #[cfg(feature = "audio")]
@@ -27,12 +26,25 @@ mod log;
 #[cfg(feature = "audio")]
 mod audio;
 
+#[macro_use]
+mod i18n;
+
+mod am;
+mod app;
 mod constants;
+mod crash;
+mod error;
 mod format;
+mod input;
 mod launcher;
+mod luma_help;
 mod mod_picker;
+mod quick_launch;
+mod saltwater;
 mod scene;
+mod version;
 use self::{
+    app::{App, Tracked, UndoStack},
     launcher::GameVer,
     scene::menu::{MenuAction, MenuState},
 };
@@ -40,13 +52,6 @@ use self::{
 /// Bindings + safe abstraction for plgldr.c
 mod plgldr;
 
-//TODO: mutex or reduce all these things to one global state struct
-static mut CONFIG: Option<format::saltwater_cfg::Config> = None;
-
-//TODO: just use a mutex please
-#[cfg(feature = "audio")]
-static mut AUDIO: Option<*const audio::AudioManager> = None;
-
 fn main() {
     let is_citra = unsafe {
         let mut citra_info = 0i64;
@@ -62,30 +67,10 @@ fn main() {
     match run(is_citra) {
         Ok(_) => {}
         Err(c) => {
-            let error = match c {
-                Error::Ctru(c) => match c {
-                    ctru::Error::Os(c) => format!("System error {:#X}", c),
-                    ctru::Error::Libc(c) => format!("libc error:\n{}", c),
-                    ctru::Error::ServiceAlreadyActive => "Service already active".to_string(),
-                    ctru::Error::OutputAlreadyRedirected => "Output already redirected".to_string(),
-                    c => format!("Unknown ctru error\n{}", c),
-                },
-                Error::Io(c) => {
-                    format!("IO error: {}", c)
-                }
-                Error::TomlDe(c) => {
-                    format!("TOML deserialize error: {}", c)
-                }
-                Error::TomlSer(c) => {
-                    format!("TOML serialize error: {}", c)
-                }
-                Error::Other(c) => c,
-            };
+            log!(Error, General, "{}", c);
+            let error = with_report(c.with_hint(), None);
             if is_citra {
-                let gfx = Gfx::new().unwrap();
-                let _ = ctru::console::Console::new(gfx.bottom_screen.borrow_mut());
-                println!("Error: {}\n\nExiting in 20 seconds...", error);
-                std::thread::sleep(Duration::from_secs(20));
+                citra_show(&format!("Error: {}", error));
             } else {
                 error_applet(error);
             }
@@ -101,105 +86,170 @@ fn run(is_citra: bool) -> error::Result<()> {
     let gfx = Gfx::new()?;
     let ps = Ps::new()?;
     let romfs = RomFS::new()?;
-    let console = Console::new(gfx.bottom_screen.borrow_mut());
 
-    log!(General, "test");
+    log::init();
+    log!(
+        Info,
+        General,
+        "Barista {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_HASH").trim()
+    );
 
     // Initialize GFX stuff
     let mut ui = BaristaUI::init();
 
     ui.set_scene(Screen::Top, scene::top_screen_scene);
+    ui.set_scene(Screen::Bottom, scene::bottom_screen_scene);
+
+    // Init Barista config
+    let settings = format::barista_cfg::BaristaConfig::from_file(app::SETTINGS_PATH)?;
+    i18n::set_language(&settings);
+    // Nothing else is any use without the plugin loader, so check it before touching anything
+    if let Some(c) = launcher::check_luma(is_citra) {
+        luma_help::show(&apt, &gfx, &mut hid, &mut ui, c);
+        return Ok(());
+    }
 
     // Init loader
-    let versions = launcher::get_available_games();
+    let versions = launcher::get_available_games()?;
+    crash::set_games(&versions);
+    // Whatever the last launch couldn't put back shouldn't keep Barista from starting, it's
+    // shown on the main menu instead
+    let mut startup_errors = vec![];
+    let mut restored = |result: Result<()>| {
+        if let Err(e) = result {
+            log!(Error, Launcher, "{}", e);
+            startup_errors.push(e.with_hint());
+        }
+    };
+    // Anything disabled last time only stays that way for that session
+    restored(launcher::restore_disabled_conflicts());
+    // Same for the mod profile that took saltwater.cfg's place
+    restored(launcher::restore_default_profile());
+    // And the plugin loader, if a vanilla launch turned it off
+    restored(launcher::restore_plugin_loader());
+    if settings.restore_rhmpatch {
+        restored(launcher::restore_stranded_rhmpatch());
+    }
+    let conflicts = launcher::find_conflicts(&versions);
 
-    let mut game_to_load: Option<GameVer> = None;
-    launcher::check_for_plgldr();
+    // Version to launch, and whether to launch it without Saltwater
+    let mut game_to_load: Option<(GameVer, bool)> = None;
 
     let mods = mod_picker::get_available_mods()?;
 
-    // Init Barista config
-    let mut settings = format::barista_cfg::BaristaConfig::from_file("sdmc:/spicerack/cfg.toml")?;
     let mut random = [0u8; 1];
     ps.generate_random_bytes(&mut random)?;
     if !settings.is_new && random == [0x69u8; 1] {
         scene::top_screen::nicole_easter_egg(&mut ui);
     }
 
-    // Init menu
-    let mut menu = MenuState::default();
-    menu.render(&console, &versions, &[], 0, 0, &settings)?;
-
-    #[allow(unused)]
-    let mut audio_player;
-
     #[allow(unused)]
     let mut ndsp;
 
     #[allow(unused)]
     #[cfg(not(feature = "audio"))]
     {
-        audio_player = ();
         ndsp = ();
     }
 
     #[cfg(feature = "audio")]
-    {
+    let audio = {
         ndsp = Ndsp::new()?;
         ndsp.set_output_mode(ndsp::OutputMode::Stereo);
 
         // Music test
-        audio_player = audio::AudioManager::new();
+        let mut audio_player = audio::AudioManager::new();
 
         // Initial values for audio player
         audio_player.load("romfs:/audio/strm/bartender_construction.bcstm".to_string());
         audio_player.play();
+        Some(audio_player)
+    };
 
-        unsafe { AUDIO = Some(&audio_player) }
+    // Init Saltwater config. One that can't be read starts over empty, but isn't saved over
+    // until the user says so, in case they'd rather fix it
+    let (mut config, config_read) =
+        match format::saltwater_cfg::Config::from_file_or_default(app::CONFIG_PATH) {
+            Ok(c) => (c, true),
+            Err(e) => {
+                log!(Error, Config, "{}", e);
+                startup_errors.push(e.with_hint());
+                (Default::default(), false)
+            }
+        };
+    // clear mods not in the current folder, save the cfg file after clearing
+    config.clear_deleted_mods(&mods);
+
+    let mut app = App {
+        ui,
+        settings: Tracked::new(settings),
+        config: Tracked::new(config),
+        mod_undo: UndoStack::default(),
+        profile: None,
+        profiles: app::find_profiles(),
+        versions,
+        mods,
+        page: 0,
+        conflicts,
+        saltwater: saltwater::Status::scan(),
+        #[cfg(feature = "audio")]
+        audio,
+    };
+    if config_read {
+        app.save_config()?;
     }
 
-    // Init Saltwater config
-    unsafe {
-        CONFIG = Some(
-            format::saltwater_cfg::Config::from_file("sdmc:/spicerack/bin/saltwater.cfg")
-                .unwrap_or_default(),
-        );
+    // Quick launch, unless something went wrong that the menu has to tell about first
+    hid.scan_input();
+    if let Some(ver) =
+        quick_launch::target(&app.settings, &app.versions, input::keys(hid.keys_held()))
+            .filter(|_| startup_errors.is_empty())
+    {
+        if quick_launch::countdown(&apt, &gfx, &mut hid, &mut app, &ver) {
+            game_to_load = Some((ver, false));
+        }
     }
-    // clear mods not in the current folder, save the cfg file after clearing
-    config().clear_deleted_mods(&mods);
-    config().to_file("sdmc:/spicerack/bin/saltwater.cfg")?;
 
-    let mut page = 0;
+    // Init menu
+    let mut menu = MenuState::default();
+    if !startup_errors.is_empty() {
+        menu.error = Some(startup_errors.join("\n"));
+    }
+    menu.render(&mut app, &[])?;
 
     // Main loop
-    while apt.main_loop() {
+    while game_to_load.is_none() && apt.main_loop() {
         gfx.wait_for_vblank();
 
         hid.scan_input();
 
-        ui.render();
+        app.ui.render();
 
-        menu.run(&hid, &console, &versions, &mods, &mut page, &mut settings)?;
+        menu.run(&hid, &mut app)?;
 
         match &menu.action {
             MenuAction::Exit => break,
             MenuAction::Run => {
-                game_to_load = Some(versions[menu.cursor as usize].clone());
+                let ver = app.versions[menu.cursor as usize].clone();
+                app.settings.last_launched = Some(ver.key());
+                app.save_settings()?;
+                game_to_load = Some((ver, false));
                 break;
             }
-            #[cfg(feature = "audio")]
-            MenuAction::ToggleAudio => {
-                if audio_player.is_playing() {
-                    audio_player.pause()
-                } else {
-                    audio_player.play()
-                }
-            }
-            MenuAction::SaveConfig => {
-                config().to_file("sdmc:/spicerack/bin/saltwater.cfg")?;
+            MenuAction::RunVanilla => {
+                game_to_load = Some((app.versions[menu.cursor as usize].clone(), true));
+                break;
             }
-            MenuAction::SaveSettings => {
-                settings.to_file("sdmc:/spicerack/cfg.toml")?;
+            #[cfg(feature = "audio")]
+            MenuAction::ToggleAudio => app.toggle_audio(),
+            MenuAction::SaveConfig => app.save_config()?,
+            MenuAction::SaveSettings => app.save_settings()?,
+            MenuAction::SaveAndExit => {
+                app.save_config()?;
+                app.save_settings()?;
+                break;
             }
             MenuAction::ChangeMenu(_)
             | MenuAction::None
@@ -207,59 +257,79 @@ fn run(is_citra: bool) -> error::Result<()> {
             | MenuAction::ChangePage(_)
             | MenuAction::ChangeIndex(..)
             | MenuAction::ToggleMod
-            | MenuAction::ToggleSetting(_) => {}
+            | MenuAction::Undo
+            | MenuAction::ChangeProfile
+            | MenuAction::ToggleSetting(_)
+            | MenuAction::CycleLaunchOption
+            | MenuAction::ToggleConflict
+            | MenuAction::InstallSaltwater
+            | MenuAction::RollbackSaltwater
+            | MenuAction::LogLevel(_)
+            | MenuAction::LogCategory
+            | MenuAction::SaveLog
+            | MenuAction::Leave
+            | MenuAction::SaveChanges
+            | MenuAction::DiscardChanges
+            | MenuAction::KeepEditing => {}
         }
     }
 
-    drop(console);
+    let App {
+        ui,
+        settings,
+        conflicts,
+        ..
+    } = app;
+
+    drop(ui);
     drop(gfx);
     drop(hid);
     drop(romfs);
     #[allow(dropping_copy_types)]
     drop(ndsp);
 
-    if let Some(c) = game_to_load {
-        launcher::launch(c, is_citra, &settings)
+    if let Some((c, _)) = &game_to_load {
+        launcher::disable_conflicts(&conflicts, &c.region)?;
+    }
+    match game_to_load {
+        Some((c, false)) => launcher::launch(c, is_citra, &settings)?,
+        Some((c, true)) => launcher::launch_vanilla(c, is_citra)?,
+        None => {}
     }
 
     Ok(())
 }
 
-fn config() -> &'static mut format::saltwater_cfg::Config {
-    unsafe { CONFIG.as_mut().expect("Config not initialized") }
-}
-
-#[cfg(feature = "audio")]
-fn audio<'a>() -> &'a audio::AudioManager {
-    unsafe { &*AUDIO.expect("Audio not initialized") }
-}
-
 fn panic_hook(info: &PanicHookInfo) {
-    error_applet(panic_message(info));
+    error_applet(with_report(panic_message(info), info.location()));
 
     process::exit(1);
 }
 
 fn citra_panic_hook(info: &PanicHookInfo) {
-    /* let mut backtrace = backtrace::Backtrace::new();
-    backtrace.resolve();
-    println!(
-        "{:?}",
-        backtrace
-            .frames()
-            .iter()
-            .map(|c| c.symbol_address())
-            .collect::<Vec<_>>()
-    ); */
-
-    println!("{}", panic_message(info));
-
-    println!("\nExiting in 20 seconds...");
-    std::thread::sleep(Duration::from_secs(20));
+    citra_show(&with_report(panic_message(info), info.location()));
 
     process::exit(1);
 }
 
+/// Citra doesn't have the error applet, so `message` goes on a console on the bottom screen
+/// instead, for long enough to read it
+fn citra_show(message: &str) {
+    // A panic from inside `run` happens with its Gfx still around, and the UI drawing over
+    // the bottom screen. libctru can still put a console on it, Gfx just won't hand it out
+    let gfx = Gfx::new();
+    let _console = match &gfx {
+        Ok(c) => Some(Console::new(c.bottom_screen.borrow_mut())),
+        Err(_) => {
+            unsafe { ctru_sys::consoleInit(ctru_sys::GFX_BOTTOM, ptr::null_mut()) };
+            None
+        }
+    };
+
+    println!("{}\n\nExiting in 20 seconds...", message);
+    std::thread::sleep(Duration::from_secs(20));
+}
+
 fn panic_message(info: &PanicHookInfo) -> String {
     let location_info = if let Some(c) = info.location() {
         format!(" at {}:{}:{}", c.file(), c.line(), c.column())
@@ -268,10 +338,18 @@ fn panic_message(info: &PanicHookInfo) -> String {
     };
 
     if let Some(c) = info.payload().downcast_ref::<&str>() {
-        format!("panic: {:?}{}\0", c, location_info)
+        format!("panic: {:?}{}", c, location_info)
     } else if let Some(c) = info.payload().downcast_ref::<String>() {
-        format!("panic: {:?}{}\0", c, location_info)
+        format!("panic: {:?}{}", c, location_info)
     } else {
-        format!("panic{}\0", location_info)
+        format!("panic{}", location_info)
+    }
+}
+
+/// Saves a crash report for `message` and adds where it went to it, for the error screen
+fn with_report(message: String, location: Option<&Location>) -> String {
+    match crash::save(&message, location) {
+        Ok(c) => format!("{}\n\nCrash report saved to\n{}", message, c),
+        Err(e) => format!("{}\n\nCouldn't save a crash report:\n{}", message, e),
     }
 }
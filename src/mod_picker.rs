@@ -1,21 +1,36 @@
-use crate::{error::Result, format::saltwater_cfg::Config};
-use std::{collections::HashMap, ffi::OsStr, fs, path::PathBuf};
+use crate::{
+    error::{FileOp, Result},
+    format::saltwater_cfg::Config,
+    Error,
+};
+use std::{collections::HashMap, ffi::OsStr, fs, io, path::PathBuf};
+
+pub const MODS_DIR: &str = "sdmc:/spicerack/mods";
 
 pub const ENTRIES_PER_PAGE: usize = 13;
 
 pub fn get_available_mods() -> Result<Vec<PathBuf>> {
+    let err = |op: FileOp| {
+        move |source: io::Error| Error::ModScan {
+            op,
+            path: MODS_DIR.to_string(),
+            source,
+        }
+    };
     let mut v = vec![];
-    let iter = match fs::read_dir("sdmc:/spicerack/mods") {
+    let iter = match fs::read_dir(MODS_DIR) {
         Ok(c) => c,
         Err(_) => {
-            fs::create_dir_all("sdmc:/spicerack/mods")?;
-            fs::read_dir("/spicerack/mods")?
+            fs::create_dir_all(MODS_DIR).map_err(err(FileOp::CreateDir))?;
+            fs::read_dir(MODS_DIR).map_err(err(FileOp::List))?
         }
     };
     for f in iter {
-        let f = f?;
+        let f = f.map_err(err(FileOp::List))?;
         let path = f.path();
-        if path.as_path().extension() == Some(OsStr::new("btk")) && f.metadata()?.is_file() {
+        if path.as_path().extension() == Some(OsStr::new("btk"))
+            && f.metadata().map_err(err(FileOp::List))?.is_file()
+        {
             v.push(path);
         }
     }
@@ -178,3 +178,12 @@ extern "C" {
 extern "C" {
     pub fn PLGLDR__SetPluginLoadParameters(parameters: *mut PluginLoadParameters) -> Result;
 }
+extern "C" {
+    pub fn PLGLDR__GetVersion(version: *mut u32_) -> Result;
+}
+extern "C" {
+    pub fn PLGLDR__GetArenaSize(size: *mut u32_) -> Result;
+}
+extern "C" {
+    pub fn PLGLDR__GetPluginPath(path: *mut ::libc::c_char) -> Result;
+}
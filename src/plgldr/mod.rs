@@ -1,112 +1,366 @@
-#![allow(dead_code)]
-
 use static_assertions::const_assert;
-use std::{ffi::CString, ptr::addr_of_mut};
+use std::fmt::{self, Display};
 
-use crate::format::barista_cfg::BaristaConfig;
+use crate::{format::barista_cfg::BaristaConfig, version::Version};
 
 #[allow(warnings)]
 mod bindings;
 
+/// Parameters passed to Saltwater through the plugin loader's 0x80-byte config area
+///
+/// Saltwater only reads the first 6 bytes. The rest is kept zeroed, so once Saltwater reads
+/// more of it, a layout version can go at the start of `reserved` with zero meaning none
 #[repr(C)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SaltwaterParams {
+    /// Magic, lets Saltwater know it was launched from Barista
     pub barista: u16,
+    /// Rename RHMPatch's code.ips back when the game exits
     pub reenable_rhmpatch: bool,
+    /// Turn the plugin loader back off when the game exits
     pub disable_plgldr: bool,
     pub loaded_msg: bool,
     pub extra_rows: bool,
-    pub null: [u8; 0x7A],
+    pub reserved: [u8; 0x7A],
 }
 
 const_assert!(std::mem::size_of::<SaltwaterParams>() == 0x80);
+const_assert!(std::mem::offset_of!(SaltwaterParams, reenable_rhmpatch) == 2);
+const_assert!(std::mem::offset_of!(SaltwaterParams, extra_rows) == 5);
+const_assert!(std::mem::offset_of!(SaltwaterParams, reserved) == 6);
 
 impl Default for SaltwaterParams {
     fn default() -> Self {
-        Self {
-            barista: 0xD06,
-            reenable_rhmpatch: false,
-            disable_plgldr: false,
-            loaded_msg: true,
-            extra_rows: false,
-            null: [0; 0x7A],
-        }
+        Self::DEFAULT
     }
 }
 
 impl SaltwaterParams {
-    pub fn apply_settings(&mut self, settings: &BaristaConfig) {
+    const DEFAULT: Self = Self {
+        barista: 0xD06,
+        reenable_rhmpatch: false,
+        disable_plgldr: false,
+        loaded_msg: true,
+        extra_rows: false,
+        reserved: [0; 0x7A],
+    };
+
+    /// Fills in the user's options. `rhmpatch_moved` and `plgldr_enabled` say what Barista had
+    /// to change to launch Saltwater, which Saltwater can then undo when the game exits
+    pub fn apply_settings(
+        &mut self,
+        settings: &BaristaConfig,
+        rhmpatch_moved: bool,
+        plgldr_enabled: bool,
+    ) {
+        self.reenable_rhmpatch = rhmpatch_moved && settings.restore_rhmpatch;
+        self.disable_plgldr = plgldr_enabled && settings.restore_plgldr;
         self.loaded_msg = settings.btk_loaded_msg;
         self.extra_rows = settings.extra_rows;
     }
+
+    /// The bytes Saltwater reads, all little endian
+    pub const fn to_bytes(&self) -> [u8; 0x80] {
+        let mut out = [0u8; 0x80];
+        let barista = self.barista.to_le_bytes();
+        out[0] = barista[0];
+        out[1] = barista[1];
+        out[2] = self.reenable_rhmpatch as u8;
+        out[3] = self.disable_plgldr as u8;
+        out[4] = self.loaded_msg as u8;
+        out[5] = self.extra_rows as u8;
+        let mut i = 0;
+        while i < self.reserved.len() {
+            out[6 + i] = self.reserved[i];
+            i += 1;
+        }
+        out
+    }
 }
 
-pub fn init() -> Result<(), i32> {
-    let result = unsafe { bindings::plgLdrInit() };
-    match result {
-        0 => Ok(()),
-        c => Err(c),
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
     }
+    true
 }
 
-pub fn exit() {
-    unsafe { bindings::plgLdrExit() }
+/// What Saltwater expects for the default parameters
+const DEFAULT_BYTES: [u8; 0x80] = {
+    let mut out = [0u8; 0x80];
+    out[0] = 0x06;
+    out[1] = 0x0D;
+    out[4] = 1;
+    out
+};
+
+/// What Saltwater expects with every flag flipped from the defaults
+const FLIPPED_BYTES: [u8; 0x80] = {
+    let mut out = DEFAULT_BYTES;
+    out[2] = 1;
+    out[3] = 1;
+    out[4] = 0;
+    out[5] = 1;
+    out
+};
+
+const_assert!(bytes_eq(
+    &SaltwaterParams::DEFAULT.to_bytes(),
+    &DEFAULT_BYTES
+));
+const_assert!(bytes_eq(
+    &SaltwaterParams {
+        reenable_rhmpatch: true,
+        disable_plgldr: true,
+        loaded_msg: false,
+        extra_rows: true,
+        ..SaltwaterParams::DEFAULT
+    }
+    .to_bytes(),
+    &FLIPPED_BYTES
+));
+
+/// `reserved` goes right after the flags, all the way to the end
+const RESERVED_BYTES: [u8; 0x80] = {
+    let mut out = DEFAULT_BYTES;
+    let mut i = 6;
+    while i < 0x80 {
+        out[i] = 0xAA;
+        i += 1;
+    }
+    out
+};
+
+const_assert!(bytes_eq(
+    &SaltwaterParams {
+        reserved: [0xAA; 0x7A],
+        ..SaltwaterParams::DEFAULT
+    }
+    .to_bytes(),
+    &RESERVED_BYTES
+));
+
+/// A 3DS result code, as returned by the plugin loader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResultCode(pub i32);
+
+impl ResultCode {
+    fn check(result: i32) -> Result<(), Error> {
+        match result {
+            0 => Ok(()),
+            c => Err(Error::Request(Self(c))),
+        }
+    }
+
+    pub fn description(&self) -> u32 {
+        self.0 as u32 & 0x3FF
+    }
+
+    pub fn module(&self) -> u32 {
+        (self.0 as u32 >> 10) & 0xFF
+    }
+
+    pub fn summary(&self) -> u32 {
+        (self.0 as u32 >> 21) & 0x3F
+    }
+
+    pub fn level(&self) -> u32 {
+        (self.0 as u32 >> 27) & 0x1F
+    }
+
+    fn summary_name(&self) -> &'static str {
+        match self.summary() {
+            0 => "success",
+            1 => "nothing happened",
+            2 => "would block",
+            3 => "out of resource",
+            4 => "not found",
+            5 => "invalid state",
+            6 => "not supported",
+            7 => "invalid argument",
+            8 => "wrong argument",
+            9 => "canceled",
+            10 => "status changed",
+            11 => "internal",
+            _ => "unknown",
+        }
+    }
 }
 
-pub fn is_enabled() -> Result<bool, i32> {
-    let mut res_ok = false;
-    let result = unsafe { bindings::PLGLDR__IsPluginLoaderEnabled(&mut res_ok) };
-    match result {
-        0 => Ok(res_ok),
-        c => Err(c),
+impl Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:#010X} ({}, module {}, description {}, level {})",
+            self.0,
+            self.summary_name(),
+            self.module(),
+            self.description(),
+            self.level()
+        )
     }
 }
 
-pub fn set_state(enabled: bool) -> Result<(), i32> {
-    let result = unsafe { bindings::PLGLDR__SetPluginLoaderState(enabled) };
-    match result {
-        0 => Ok(()),
-        c => Err(c),
+/// Something the plugin loader couldn't do
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// plg:ldr couldn't be opened, Luma3DS is missing or too old
+    Unavailable(ResultCode),
+    /// plg:ldr refused a request
+    Request(ResultCode),
+    /// A path is longer than the `max` bytes the plugin loader has room for
+    PathTooLong { path: String, max: usize },
+    /// A path has a NUL in the middle
+    PathHasNul(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unavailable(c) => write!(f, "Couldn't open the plugin loader: {}", c),
+            Self::Request(c) => write!(f, "The plugin loader returned an error: {}", c),
+            Self::PathTooLong { path, max } => write!(
+                f,
+                "{} is too long, it can be {} characters at most",
+                path, max
+            ),
+            Self::PathHasNul(c) => write!(f, "{} has a NUL character in it", c),
+        }
     }
 }
 
-static mut PARAMS: bindings::PluginLoadParameters = bindings::PluginLoadParameters {
-    noFlash: false,
-    lowTitleId: 0,
-    path: [0; 256],
-    config: [0; 32],
-};
+impl std::error::Error for Error {}
 
-pub fn set_params(
-    no_flash: bool,
-    low_title_id: u32,
-    path: CString,
-    config: SaltwaterParams,
-) -> Result<(), i32> {
-    let path = path.as_bytes();
-    if path.len() > 256 {
-        panic!(
-            "Path to call plugin loader is too long, {} characters",
-            path.len()
-        )
+/// What the plugin loader can tell about itself, for diagnostics
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    pub enabled: bool,
+    /// Numbered separately from Luma3DS
+    pub version: Version,
+    /// Memory set aside for plugins, in bytes
+    pub arena_size: u32,
+    /// The last plugin the loader was asked to load, if any. It keeps this around after the
+    /// game closes, so it doesn't mean a plugin is running
+    pub last_plugin: Option<String>,
+}
+
+/// Open handle to Luma's plugin loader service, closed when dropped
+///
+/// Binds commands 2 to 4 and 8 to 10 (state, load parameters, version, arena size, last
+/// plugin path). The rest are left out on purpose:
+/// - 1 (LoadPlugin) is sent by Luma itself when a title starts
+/// - 5 to 7 (DisplayMenu, DisplayMessage, DisplayErrMessage) are for a running plugin, they
+///   wait on an event only the plugin's process gets
+/// - the ones after 10 (menu blocking, swap and exception settings) differ between Luma
+///   releases, so one only gets bound along with a [`crate::launcher::MIN_LUMA`] that has it
+///
+/// plg:ldr has no command saying whether a plugin is loaded or what it is, the last plugin
+/// path is as close as it gets. `CpuRegisters` and `FpuRegisters` in the bindings come from
+/// libctru's headers, the plugin loader doesn't take them
+pub struct PluginLoader(());
+
+impl PluginLoader {
+    /// Fails if plg:ldr isn't there, without keeping anything open
+    pub fn new() -> Result<Self, Error> {
+        let result = unsafe { bindings::plgLdrInit() };
+        match result {
+            0 => Ok(Self(())),
+            c => Err(Error::Unavailable(ResultCode(c))),
+        }
+    }
+
+    pub fn is_enabled(&self) -> Result<bool, Error> {
+        let mut res_ok = false;
+        ResultCode::check(unsafe { bindings::PLGLDR__IsPluginLoaderEnabled(&mut res_ok) })?;
+        Ok(res_ok)
+    }
+
+    pub fn set_state(&self, enabled: bool) -> Result<(), Error> {
+        ResultCode::check(unsafe { bindings::PLGLDR__SetPluginLoaderState(enabled) })
     }
-    let mut path_bytes = [0u8; 256];
-    for (c, byte) in path.iter().enumerate() {
-        path_bytes[c] = *byte;
+
+    pub fn version(&self) -> Result<Version, Error> {
+        let mut version = 0;
+        ResultCode::check(unsafe { bindings::PLGLDR__GetVersion(&mut version) })?;
+        Ok(Version::from_packed(version))
+    }
+
+    /// Memory set aside for plugins, in bytes
+    pub fn arena_size(&self) -> Result<u32, Error> {
+        let mut size = 0;
+        ResultCode::check(unsafe { bindings::PLGLDR__GetArenaSize(&mut size) })?;
+        Ok(size)
+    }
+
+    /// Path of the last plugin the loader was asked to load, running or not. None if there
+    /// hasn't been one since the console was turned on
+    pub fn last_plugin_path(&self) -> Result<Option<String>, Error> {
+        // 255 bytes are written at most, the last one stays as the NUL terminator
+        let mut path = [0u8; 256];
+        ResultCode::check(unsafe { bindings::PLGLDR__GetPluginPath(path.as_mut_ptr().cast()) })?;
+
+        let len = path.iter().position(|c| *c == 0).unwrap_or(path.len());
+        let path = &path[..len];
+        Ok((!path.is_empty()).then(|| String::from_utf8_lossy(path).into_owned()))
+    }
+
+    pub fn status(&self) -> Result<Status, Error> {
+        Ok(Status {
+            enabled: self.is_enabled()?,
+            version: self.version()?,
+            arena_size: self.arena_size()?,
+            last_plugin: self.last_plugin_path()?,
+        })
     }
 
-    let config = unsafe { std::mem::transmute::<SaltwaterParams, [u32; 32]>(config) };
+    /// Makes the plugin loader load the plugin at `path` (on the SD, without `sdmc:`) the next
+    /// time the title with `low_title_id` starts, passing it `config`
+    pub fn set_params(
+        &self,
+        no_flash: bool,
+        low_title_id: u32,
+        path: &str,
+        config: SaltwaterParams,
+    ) -> Result<(), Error> {
+        if path.contains('\0') {
+            return Err(Error::PathHasNul(path.to_string()));
+        }
+        let mut path_bytes = [0u8; 256];
+        // Keep the last byte as the NUL terminator
+        if path.len() >= path_bytes.len() {
+            return Err(Error::PathTooLong {
+                path: path.to_string(),
+                max: path_bytes.len() - 1,
+            });
+        }
+        path_bytes[..path.len()].copy_from_slice(path.as_bytes());
+
+        let bytes = config.to_bytes();
+        let mut config = [0u32; 32];
+        for (word, chunk) in config.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
 
-    let result = unsafe {
-        PARAMS = bindings::PluginLoadParameters {
+        // plgldr copies the parameters into the IPC buffer, so they only need to outlive the call
+        let mut params = bindings::PluginLoadParameters {
             noFlash: no_flash,
             lowTitleId: low_title_id,
             path: path_bytes,
             config,
         };
-        bindings::PLGLDR__SetPluginLoadParameters(addr_of_mut!(PARAMS))
-    };
-    match result {
-        0 => Ok(()),
-        c => Err(c),
+        ResultCode::check(unsafe { bindings::PLGLDR__SetPluginLoadParameters(&mut params) })
+    }
+}
+
+impl Drop for PluginLoader {
+    fn drop(&mut self) {
+        unsafe { bindings::plgLdrExit() }
     }
 }
@@ -0,0 +1,62 @@
+// Quick launch: start the last played version without going through the menu, as long as
+// nobody cancels the countdown
+
+use ctru::services::{apt::Apt, gfx::Gfx, hid::Hid};
+
+use crate::{
+    app::App,
+    format::barista_cfg::BaristaConfig,
+    input::{self, Binding, Keys},
+    launcher::GameVer,
+    scene::menu::{backend::MenuRenderer, render::MenuView},
+};
+
+const COUNTDOWN_SECONDS: u32 = 3;
+const FRAMES_PER_SECOND: u32 = 60;
+
+/// The version to launch right away, if quick launch is on or its button is being held
+pub fn target(settings: &BaristaConfig, versions: &[GameVer], held: Keys) -> Option<GameVer> {
+    if !settings.quick_launch && !settings.bindings.matches(Binding::QuickLaunch, held) {
+        return None;
+    }
+    let last = settings.last_launched.as_ref()?;
+    versions.iter().find(|c| &c.key() == last).cloned()
+}
+
+/// Counts down to launching `ver`. Returns false if it got cancelled
+pub fn countdown(apt: &Apt, gfx: &Gfx, hid: &mut Hid, app: &mut App, ver: &GameVer) -> bool {
+    let bindings = app.settings.bindings.clone();
+    let mut frames = 0;
+    let mut shown = None;
+
+    while apt.main_loop() {
+        gfx.wait_for_vblank();
+        hid.scan_input();
+        app.ui.render();
+
+        let down = input::keys(hid.keys_down());
+        if bindings.matches(Binding::Back, down) || down.contains(Keys::TOUCH) {
+            return false;
+        }
+        if bindings.matches(Binding::Confirm, down) {
+            return true;
+        }
+
+        let left = COUNTDOWN_SECONDS - frames / FRAMES_PER_SECOND;
+        if left == 0 {
+            return true;
+        }
+        if shown != Some(left) {
+            app.ui.show(&MenuView {
+                title: tr!("text_menu.title") + &tr!("quick_launch.title"),
+                header: tr!("quick_launch.countdown", ver, left),
+                footer: tr!("quick_launch.cancel"),
+                back_button: true,
+                ..Default::default()
+            });
+            shown = Some(left);
+        }
+        frames += 1;
+    }
+    false
+}
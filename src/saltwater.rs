@@ -0,0 +1,257 @@
+// Keeping Saltwater itself installed: reading the version out of its .3gx and installing
+// updates dropped in /spicerack/updates, keeping the previous copy around to roll back to
+
+use std::{
+    fmt::{self, Display},
+    fs::{self, File},
+    io::{Error as IoError, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    launcher::DEFAULT_PLUGIN,
+    plgldr::{self, PluginLoader},
+    version::Version,
+    Error, Result,
+};
+
+pub const UPDATES_DIR: &str = "sdmc:/spicerack/updates";
+const BACKUP_SUFFIX: &str = ".bak";
+const TEMP_SUFFIX: &str = ".new";
+
+const MAGIC: &[u8; 8] = b"3GX$0002";
+
+/// Where Saltwater gets installed, with the `sdmc:` Barista needs to open it
+pub fn plugin_path() -> String {
+    format!("sdmc:{}", DEFAULT_PLUGIN)
+}
+
+fn backup_path() -> String {
+    plugin_path() + BACKUP_SUFFIX
+}
+
+/// What the header of a .3gx says about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+    pub title: String,
+    pub version: Version,
+    /// FNV-1a of the whole file, to tell apart builds with the same version
+    pub hash: u32,
+}
+
+impl Display for PluginInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} ({:08X})", self.title, self.version, self.hash)
+    }
+}
+
+impl PluginInfo {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut data = vec![];
+        File::open(path)?.read_to_end(&mut data)?;
+        let invalid = || Error::NotPlugin(path.display().to_string());
+
+        if !data.starts_with(MAGIC) {
+            return Err(invalid());
+        }
+        let word = |offset: usize| -> Result<u32> {
+            offset
+                .checked_add(4)
+                .and_then(|end| data.get(offset..end))
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .ok_or_else(invalid)
+        };
+
+        let version = word(0x8)?;
+        let title_len = word(0x18)? as usize;
+        let title_offset = word(0x1C)? as usize;
+        let title = title_offset
+            .checked_add(title_len)
+            .and_then(|end| data.get(title_offset..end))
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            title: String::from_utf8_lossy(title)
+                .trim_end_matches('\0')
+                .to_string(),
+            version: Version::from_packed(version),
+            hash: fnv1a(&data),
+        })
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811C9DC5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// What was being done to Saltwater's files, for [`Error::SaltwaterInstall`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallOp {
+    CreateDir,
+    /// Copying the update next to the installed plugin
+    Copy,
+    /// Moving the installed plugin out of the way to keep as the backup
+    Backup,
+    /// Putting the new plugin in place
+    Replace,
+    RemoveUpdate,
+    Rollback,
+}
+
+impl Display for InstallOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::CreateDir => "create the plugin folder",
+            Self::Copy => "copy the update",
+            Self::Backup => "back up the installed Saltwater",
+            Self::Replace => "install the new Saltwater",
+            Self::RemoveUpdate => "remove the installed update",
+            Self::Rollback => "restore the previous Saltwater",
+        })
+    }
+}
+
+fn failed(op: InstallOp, path: impl AsRef<Path>) -> impl FnOnce(IoError) -> Error {
+    let path = path.as_ref().display().to_string();
+    move |source| Error::SaltwaterInstall { op, path, source }
+}
+
+/// A .3gx waiting to be installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    pub path: PathBuf,
+    pub info: PluginInfo,
+}
+
+impl Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        write!(f, "{} ({})", name, self.info.version)
+    }
+}
+
+/// The state of the Saltwater install, redone with [`Status::scan`] whenever it changes
+#[derive(Debug, Default)]
+pub struct Status {
+    /// None if Saltwater is missing or isn't a plugin
+    pub installed: Option<PluginInfo>,
+    /// Plugins in the updates folder, anything that isn't a valid .3gx is left out
+    pub updates: Vec<Update>,
+    /// Version of the copy that'd be restored by rolling back, if there is one
+    pub backup: Option<PluginInfo>,
+    /// None if the plugin loader couldn't be asked
+    pub loader: Option<plgldr::Status>,
+    /// Other Saltwater builds next to the installed one, as the SD paths the plugin loader
+    /// takes, for versions set to launch with one of them
+    pub builds: Vec<String>,
+}
+
+impl Status {
+    pub fn scan() -> Self {
+        let installed = match PluginInfo::from_file(plugin_path()) {
+            Ok(c) => Some(c),
+            Err(e) => {
+                log!(Warn, Launcher, "Saltwater isn't installed properly: {}", e);
+                None
+            }
+        };
+
+        let mut updates = vec![];
+        if let Ok(dir) = fs::read_dir(UPDATES_DIR) {
+            for entry in dir.flatten() {
+                let path = entry.path();
+                if !path.extension().is_some_and(|c| c == "3gx") {
+                    continue;
+                }
+                match PluginInfo::from_file(&path) {
+                    Ok(info) => updates.push(Update { path, info }),
+                    Err(e) => log!(Warn, Launcher, "Skipping update: {}", e),
+                }
+            }
+        }
+        updates.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut builds = vec![];
+        let bin = Path::new(DEFAULT_PLUGIN).parent().unwrap_or(Path::new("/"));
+        if let Ok(dir) = fs::read_dir(format!("sdmc:{}", bin.display())) {
+            for entry in dir.flatten() {
+                let path = bin.join(entry.file_name());
+                if path.extension().is_some_and(|c| c == "3gx") && path != Path::new(DEFAULT_PLUGIN)
+                {
+                    builds.push(path.display().to_string());
+                }
+            }
+        }
+        builds.sort();
+
+        Self {
+            installed,
+            updates,
+            backup: PluginInfo::from_file(backup_path()).ok(),
+            loader: PluginLoader::new().and_then(|c| c.status()).ok(),
+            builds,
+        }
+    }
+}
+
+/// Replaces the installed Saltwater with `update`, which is removed from the updates folder.
+/// The copy being replaced becomes the one [`rollback`] goes back to
+pub fn install(update: &Update) -> Result<()> {
+    log!(Info, Launcher, "Installing Saltwater from {}", update);
+    let plugin = plugin_path();
+    let backup = backup_path();
+    let temp = plugin.clone() + TEMP_SUFFIX;
+
+    if let Some(c) = Path::new(&plugin).parent() {
+        fs::create_dir_all(c).map_err(failed(InstallOp::CreateDir, c))?;
+    }
+    // Copy it next to the plugin first, so a failed copy doesn't leave a broken Saltwater
+    fs::copy(&update.path, &temp).map_err(failed(InstallOp::Copy, &update.path))?;
+
+    let had_plugin = fs::metadata(&plugin).is_ok();
+    if had_plugin {
+        if fs::metadata(&backup).is_ok() {
+            fs::remove_file(&backup).map_err(failed(InstallOp::Backup, &backup))?;
+        }
+        fs::rename(&plugin, &backup).map_err(failed(InstallOp::Backup, &plugin))?;
+    }
+    if let Err(source) = fs::rename(&temp, &plugin) {
+        if had_plugin {
+            fs::rename(&backup, &plugin).map_err(failed(InstallOp::Rollback, &backup))?;
+        }
+        let _ = fs::remove_file(&temp);
+        return Err(failed(InstallOp::Replace, &update.path)(source));
+    }
+
+    fs::remove_file(&update.path).map_err(failed(InstallOp::RemoveUpdate, &update.path))?;
+    Ok(())
+}
+
+/// Swaps the installed Saltwater with the previous one. Doing it again undoes it
+pub fn rollback() -> Result<()> {
+    log!(Info, Launcher, "Rolling back Saltwater");
+    let plugin = plugin_path();
+    let backup = backup_path();
+    let temp = plugin.clone() + TEMP_SUFFIX;
+
+    if fs::metadata(&backup).is_err() {
+        return Err(Error::NoSaltwaterBackup);
+    }
+    let had_plugin = fs::metadata(&plugin).is_ok();
+    if had_plugin {
+        fs::rename(&plugin, &temp).map_err(failed(InstallOp::Backup, &plugin))?;
+    }
+    if let Err(source) = fs::rename(&backup, &plugin) {
+        if had_plugin {
+            fs::rename(&temp, &plugin).map_err(failed(InstallOp::Replace, &temp))?;
+        }
+        return Err(failed(InstallOp::Rollback, &backup)(source));
+    }
+    if had_plugin {
+        fs::rename(&temp, &backup).map_err(failed(InstallOp::Backup, &temp))?;
+    }
+    Ok(())
+}
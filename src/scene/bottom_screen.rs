@@ -0,0 +1,196 @@
+use ui_lib::{BaristaUI, Button, List, Rectangle, Scene, Screen, Text};
+
+use super::menu::{
+    backend::{MenuRenderer, TouchTarget},
+    render::MenuView,
+};
+
+const SCREEN_WIDTH: u16 = 320;
+const SCREEN_HEIGHT: u16 = 240;
+
+const TITLE_BAR_HEIGHT: u16 = 24;
+const FOOTER_HEIGHT: u16 = 16;
+const ROW_HEIGHT: u16 = 16;
+const MARGIN: u16 = 6;
+const BUTTON_WIDTH: u16 = 56;
+const PAGE_BUTTON_WIDTH: u16 = 24;
+
+pub fn bottom_screen_scene(ui: &BaristaUI, screen: Screen) -> Scene {
+    let mut scene = Scene::new(ui, screen, None);
+
+    scene.add_object(
+        "title_bar",
+        Rectangle {
+            x: 0,
+            y: 0,
+            width: SCREEN_WIDTH,
+            height: TITLE_BAR_HEIGHT,
+            color: 0xFF6AB4F0,
+            depth: 0.0,
+        },
+    );
+    scene.add_object("title", Text::new("Barista".to_string(), MARGIN, 4, 16));
+    scene.add_object(
+        "header",
+        Text::new(" ".to_string(), MARGIN, TITLE_BAR_HEIGHT + MARGIN, 12),
+    );
+    scene.add_object(
+        "list",
+        List::new(
+            MARGIN,
+            TITLE_BAR_HEIGHT + MARGIN,
+            SCREEN_WIDTH - MARGIN * 2,
+            ROW_HEIGHT,
+            0,
+            13,
+        ),
+    );
+    scene.add_object(
+        "footer",
+        Text::new(" ".to_string(), MARGIN, SCREEN_HEIGHT - FOOTER_HEIGHT, 11),
+    );
+
+    // Touch buttons, on top of everything else
+    scene.add_object(
+        "save",
+        Button::new(
+            tr!("text_menu.save"),
+            SCREEN_WIDTH - (BUTTON_WIDTH + 2) * 2,
+            2,
+            BUTTON_WIDTH,
+            TITLE_BAR_HEIGHT - 4,
+            12,
+        ),
+    );
+    scene.add_object(
+        "back",
+        Button::new(
+            tr!("text_menu.back"),
+            SCREEN_WIDTH - (BUTTON_WIDTH + 2),
+            2,
+            BUTTON_WIDTH,
+            TITLE_BAR_HEIGHT - 4,
+            12,
+        ),
+    );
+    scene.add_object(
+        "undo",
+        Button::new(
+            tr!("text_menu.undo"),
+            SCREEN_WIDTH - (PAGE_BUTTON_WIDTH + 2) * 2 - (BUTTON_WIDTH + 2),
+            SCREEN_HEIGHT - FOOTER_HEIGHT,
+            BUTTON_WIDTH,
+            FOOTER_HEIGHT - 2,
+            12,
+        ),
+    );
+    scene.add_object(
+        "prev_page",
+        Button::new(
+            "<".to_string(),
+            SCREEN_WIDTH - (PAGE_BUTTON_WIDTH + 2) * 2,
+            SCREEN_HEIGHT - FOOTER_HEIGHT,
+            PAGE_BUTTON_WIDTH,
+            FOOTER_HEIGHT - 2,
+            12,
+        ),
+    );
+    scene.add_object(
+        "next_page",
+        Button::new(
+            ">".to_string(),
+            SCREEN_WIDTH - (PAGE_BUTTON_WIDTH + 2),
+            SCREEN_HEIGHT - FOOTER_HEIGHT,
+            PAGE_BUTTON_WIDTH,
+            FOOTER_HEIGHT - 2,
+            12,
+        ),
+    );
+
+    scene
+}
+
+/// Updates the bottom screen scene to show `view`
+pub fn show_menu(ui: &mut BaristaUI, view: &MenuView) {
+    // citro2d can't allocate empty text buffers
+    let or_blank = |c: &str| {
+        if c.is_empty() {
+            " ".to_string()
+        } else {
+            c.to_string()
+        }
+    };
+
+    let title: &mut Text = ui
+        .downcast_object_mut(Screen::Bottom, "title")
+        .expect("Bottom screen scene is not barista::scene::bottom_screen");
+    title.change_text(or_blank(&view.title));
+
+    let header: &mut Text = ui
+        .downcast_object_mut(Screen::Bottom, "header")
+        .expect("Bottom screen scene is not barista::scene::bottom_screen");
+    header.change_text(or_blank(&view.header));
+    let list_y = if view.header.is_empty() {
+        header.y
+    } else {
+        header.y + header.height() + MARGIN
+    };
+
+    let footer: &mut Text = ui
+        .downcast_object_mut(Screen::Bottom, "footer")
+        .expect("Bottom screen scene is not barista::scene::bottom_screen");
+    footer.change_text(or_blank(&view.footer));
+
+    let list: &mut List = ui
+        .downcast_object_mut(Screen::Bottom, "list")
+        .expect("Bottom screen scene is not barista::scene::bottom_screen");
+    list.y = list_y;
+    list.visible_rows =
+        (SCREEN_HEIGHT.saturating_sub(list_y + FOOTER_HEIGHT) / ROW_HEIGHT).max(1) as usize;
+    list.set_items(&view.items);
+    list.set_cursor(view.cursor);
+
+    for (name, visible, label) in [
+        ("save", view.save_button, Some(tr!("text_menu.save"))),
+        ("back", view.back_button, Some(tr!("text_menu.back"))),
+        ("undo", view.undo_button, Some(tr!("text_menu.undo"))),
+        ("prev_page", view.page_buttons, None),
+        ("next_page", view.page_buttons, None),
+    ] {
+        let button: &mut Button = ui
+            .downcast_object_mut(Screen::Bottom, name)
+            .expect("Bottom screen scene is not barista::scene::bottom_screen");
+        button.visible = visible;
+        if let Some(label) = label {
+            button.set_label(label);
+        }
+    }
+}
+
+impl MenuRenderer for BaristaUI {
+    fn show(&mut self, view: &MenuView) {
+        show_menu(self, view)
+    }
+
+    fn drag_list(&mut self, dy: i32) {
+        if let Some(list) = self.downcast_object_mut::<List>(Screen::Bottom, "list") {
+            list.drag(dy);
+        }
+    }
+
+    fn touched(&self, x: u16, y: u16) -> Option<TouchTarget> {
+        match self.touched_object(Screen::Bottom, x, y)? {
+            "list" => self
+                .downcast_object::<List>(Screen::Bottom, "list")
+                .filter(|c| c.cursor().is_some())
+                .and_then(|c| c.row_at(x, y))
+                .map(TouchTarget::Row),
+            "back" => Some(TouchTarget::Back),
+            "save" => Some(TouchTarget::Save),
+            "undo" => Some(TouchTarget::Undo),
+            "prev_page" => Some(TouchTarget::PrevPage),
+            "next_page" => Some(TouchTarget::NextPage),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// What the menu needs from the outside world. MenuState only talks to these traits, so it
+// can be driven by the real HID/UI on console or by scripted input in tests (see headless.rs)
+
+use ctru::services::hid::Hid;
+
+use super::render::MenuView;
+use crate::input::{self, Keys};
+
+pub trait MenuInput {
+    fn keys_down(&self) -> Keys;
+    fn keys_held(&self) -> Keys;
+    fn touch_position(&self) -> (u16, u16);
+}
+
+/// Something on the menu screen that can be tapped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchTarget {
+    Row(usize),
+    Back,
+    Save,
+    Undo,
+    PrevPage,
+    NextPage,
+}
+
+pub trait MenuRenderer {
+    fn show(&mut self, view: &MenuView);
+
+    /// Scrolls the item list by `dy` pixels, without moving the cursor
+    fn drag_list(&mut self, dy: i32);
+
+    fn touched(&self, x: u16, y: u16) -> Option<TouchTarget>;
+}
+
+impl MenuInput for Hid {
+    fn keys_down(&self) -> Keys {
+        input::keys(Hid::keys_down(self))
+    }
+
+    fn keys_held(&self) -> Keys {
+        input::keys(Hid::keys_held(self))
+    }
+
+    fn touch_position(&self) -> (u16, u16) {
+        Hid::touch_position(self)
+    }
+}
@@ -0,0 +1,280 @@
+// Runs MenuState without anyone at the console: input comes from a script of frames (see
+// barista_input, whose own tests run on the host), and every rendered screen is written to a
+// text buffer, so menu flows can be checked by the test runner
+
+use std::{cell::Cell, collections::HashMap, fmt::Write, path::PathBuf};
+
+use input_lib::{Frame, Keys, ScriptedInput};
+
+use crate::{
+    app::{App, Tracked, UndoStack},
+    format::{barista_cfg::BaristaConfig, saltwater_cfg::Config},
+    launcher::{GameRegion, GameVer},
+    saltwater, Result,
+};
+
+use super::{
+    backend::{MenuInput, MenuRenderer, TouchTarget},
+    render::MenuView,
+    MenuAction, MenuState, SubMenu, Unsaved,
+};
+
+impl MenuInput for ScriptedInput {
+    fn keys_down(&self) -> Keys {
+        ScriptedInput::keys_down(self)
+    }
+
+    fn keys_held(&self) -> Keys {
+        ScriptedInput::keys_held(self)
+    }
+
+    fn touch_position(&self) -> (u16, u16) {
+        ScriptedInput::touch_position(self)
+    }
+}
+
+/// Writes every shown menu to `output` as plain text
+#[derive(Clone, Debug, Default)]
+pub struct TextRenderer {
+    pub output: String,
+    pub last_view: Option<MenuView>,
+    /// What every tap lands on, wherever it is
+    pub touch_target: Cell<Option<TouchTarget>>,
+}
+
+impl MenuRenderer for TextRenderer {
+    fn show(&mut self, view: &MenuView) {
+        // Consecutive identical frames would just be noise
+        if self.last_view.as_ref() == Some(view) {
+            return;
+        }
+        let _ = writeln!(self.output, "{}", view);
+        self.last_view = Some(view.clone());
+    }
+
+    fn drag_list(&mut self, dy: i32) {
+        let _ = writeln!(self.output, "(drag {})", dy);
+    }
+
+    fn touched(&self, _x: u16, _y: u16) -> Option<TouchTarget> {
+        self.touch_target.get()
+    }
+}
+
+impl std::fmt::Display for MenuView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "== {} ==", self.title)?;
+        if !self.header.is_empty() {
+            writeln!(f, "{}", self.header)?;
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            let marker = if self.cursor == Some(i) { ">" } else { " " };
+            writeln!(f, "{} {}", marker, item)?;
+        }
+        if !self.footer.is_empty() {
+            writeln!(f, "{}", self.footer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the menu until the script runs out or something leaves the menu loop, returning the
+/// action that stopped it. Saving only marks the changes as saved, since there's no SD card
+/// to write them to, and anything else that needs one fails the test
+pub fn drive(
+    menu: &mut MenuState,
+    input: &mut ScriptedInput,
+    app: &mut App<TextRenderer>,
+) -> Result<MenuAction> {
+    loop {
+        menu.run(input, app)?;
+        match &menu.action {
+            // What main does after every frame
+            MenuAction::SaveConfig => app.config.mark_saved(),
+            MenuAction::SaveSettings => app.settings.mark_saved(),
+            MenuAction::SaveAndExit => {
+                app.config.mark_saved();
+                app.settings.mark_saved();
+                return Ok(menu.action.clone());
+            }
+            MenuAction::Exit | MenuAction::Run | MenuAction::RunVanilla => {
+                return Ok(menu.action.clone())
+            }
+            #[cfg(feature = "audio")]
+            MenuAction::ToggleAudio => app.toggle_audio(),
+
+            // MenuState::run already went to the SD card for these
+            MenuAction::ChangeProfile
+            | MenuAction::InstallSaltwater
+            | MenuAction::RollbackSaltwater
+            | MenuAction::SaveLog => panic!(
+                "{:?} reads or writes the SD card, which headless runs don't have",
+                menu.action
+            ),
+
+            // Nothing but the menu itself changes
+            MenuAction::None
+            | MenuAction::ChangeMenu(_)
+            | MenuAction::UpdateScreen
+            | MenuAction::Leave
+            | MenuAction::SaveChanges
+            | MenuAction::DiscardChanges
+            | MenuAction::KeepEditing
+            | MenuAction::ToggleConflict
+            | MenuAction::ToggleSetting(_)
+            | MenuAction::CycleLaunchOption
+            | MenuAction::ChangePage(_)
+            | MenuAction::ToggleMod
+            | MenuAction::ChangeIndex(..)
+            | MenuAction::Undo
+            | MenuAction::LogLevel(_)
+            | MenuAction::LogCategory => {}
+        }
+        if !input.advance() {
+            return Ok(menu.action.clone());
+        }
+    }
+}
+
+fn app(mods: &[&str]) -> App<TextRenderer> {
+    App {
+        ui: TextRenderer::default(),
+        settings: Tracked::new(BaristaConfig::default()),
+        config: Tracked::new(Config::default()),
+        profile: None,
+        profiles: vec![],
+        mod_undo: UndoStack::default(),
+        versions: vec![],
+        mods: mods.iter().map(PathBuf::from).collect(),
+        page: 0,
+        conflicts: vec![],
+        saltwater: saltwater::Status::default(),
+        #[cfg(feature = "audio")]
+        audio: None,
+    }
+}
+
+#[test]
+fn enable_mod_move_it_and_save() {
+    let mut app = app(&["a.btk", "b.btk", "c.btk", "d.btk"]);
+    let mut menu = MenuState::default();
+    let mut input = ScriptedInput::presses(&[
+        // Set up mods
+        Keys::DPAD_DOWN,
+        Keys::A,
+        // Enable the third mod, it goes in the first free slot
+        Keys::DPAD_DOWN,
+        Keys::DPAD_DOWN,
+        Keys::A,
+        // Move it two slots up
+        Keys::DPAD_RIGHT,
+        Keys::DPAD_RIGHT,
+        // Leave, saving from the unsaved changes prompt
+        Keys::B,
+        Keys::A,
+    ]);
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+
+    assert_eq!(app.config.btks, HashMap::from([(2, "c".to_string())]));
+    assert!(!app.config.is_dirty());
+    assert_eq!(menu.sub_menu, SubMenu::Main);
+    assert!(app.ui.output.contains("002 c.btk"));
+}
+
+#[test]
+fn log_combo_asks_about_unsaved_changes() {
+    let mut app = app(&["a.btk"]);
+    let mut menu = MenuState::default();
+    let mut input = ScriptedInput::presses(&[Keys::DPAD_DOWN, Keys::A, Keys::A, Keys::SELECT]);
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+    assert_eq!(menu.sub_menu, SubMenu::Unsaved(Unsaved::Config));
+
+    // Discarding carries on to the log
+    let mut input = ScriptedInput::presses(&[Keys::DPAD_DOWN, Keys::A]);
+    drive(&mut menu, &mut input, &mut app).unwrap();
+    assert!(app.config.btks.is_empty());
+    assert_eq!(menu.sub_menu, SubMenu::Log);
+}
+
+#[test]
+fn tap_selects_then_activates() {
+    let mut app = app(&[]);
+    let mut menu = MenuState::default();
+    app.ui.touch_target.set(Some(TouchTarget::Row(1)));
+    let tap = [Frame::touch(160, 60), Frame::default()];
+    let mut input = ScriptedInput::new([tap, tap].concat());
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+
+    assert_eq!(menu.sub_menu, SubMenu::SetUp(false));
+}
+
+#[test]
+fn exit_with_unsaved_changes_asks_first() {
+    let mut app = app(&["a.btk"]);
+    let mut menu = MenuState::default();
+    let mut input = ScriptedInput::presses(&[Keys::DPAD_DOWN, Keys::A, Keys::A, Keys::START]);
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+    assert_eq!(menu.sub_menu, SubMenu::Unsaved(Unsaved::Exit));
+
+    // Discard
+    let mut input = ScriptedInput::presses(&[Keys::DPAD_DOWN, Keys::A]);
+    assert_eq!(
+        drive(&mut menu, &mut input, &mut app).unwrap(),
+        MenuAction::Exit
+    );
+}
+
+#[test]
+fn exiting_from_the_exit_prompt_backs_out() {
+    let mut app = app(&["a.btk"]);
+    let mut menu = MenuState::default();
+    let mut input =
+        ScriptedInput::presses(&[Keys::DPAD_DOWN, Keys::A, Keys::A, Keys::START, Keys::START]);
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+    assert_eq!(menu.sub_menu, SubMenu::SetUp(false));
+    assert!(app.config.is_dirty());
+}
+
+#[test]
+fn pick_a_profile_for_a_version_and_save() {
+    let mut app = app(&[]);
+    app.versions = vec![GameVer {
+        region: GameRegion::US,
+        is_digital: true,
+        version: 0,
+        update: None,
+    }];
+    let mut menu = MenuState::default();
+    let mut input = ScriptedInput::presses(&[
+        // Settings, counting from the bottom of the main menu
+        Keys::DPAD_UP,
+        Keys::DPAD_UP,
+        Keys::DPAD_UP,
+        Keys::A,
+        // Per-version options, right above Back
+        Keys::DPAD_UP,
+        Keys::DPAD_UP,
+        Keys::A,
+        // A new profile, the default one, then the new one again
+        Keys::A,
+        Keys::A,
+        Keys::A,
+        // Back to Settings doesn't ask, leaving Settings does
+        Keys::B,
+        Keys::B,
+        Keys::A,
+    ]);
+
+    drive(&mut menu, &mut input, &mut app).unwrap();
+
+    let options = app.settings.versions.get("US-digital").unwrap();
+    assert_eq!(options.profile.as_deref(), Some("profile1"));
+    assert_eq!(options.plugin, None);
+    assert!(!app.settings.is_dirty());
+    assert_eq!(menu.sub_menu, SubMenu::Main);
+}
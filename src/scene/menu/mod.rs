@@ -1,11 +1,20 @@
 // Menu: Let's Get This Done For The First Release Edition
 // Wonder if anything from here will be salvageable
 
-use crate::launcher::GameVer;
-use ctru::services::hid::KeyPad;
-
+use crate::{
+    launcher::{Conflict, GameVer},
+    log,
+    saltwater::Update,
+};
+use input_lib::{HoldController, TouchController};
+use tree::{Menu, Setting};
+
+pub mod backend;
+#[cfg(test)]
+mod headless;
 pub mod render;
 pub mod run;
+pub mod tree;
 
 #[derive(Clone, Debug)]
 pub struct MenuState {
@@ -13,105 +22,49 @@ pub struct MenuState {
     pub cursor: u32,
     pub action: MenuAction,
     pub hold_controller: HoldController,
-}
-
-#[derive(Clone, Debug, PartialEq, Default)]
-pub struct HoldController {
-    pub up: Option<u32>,
-    pub down: Option<u32>,
-    pub left: Option<u32>,
-    pub right: Option<u32>,
-}
-
-impl HoldController {
-    const FIRST_PRESS_TIME: u32 = 15;
-    const LOOP_PRESS_TIME: u32 = 4;
-
-    pub fn update(&mut self, keys: KeyPad) {
-        if keys.contains(KeyPad::DPAD_UP) {
-            if let Some(c) = &mut self.up {
-                *c += 1;
-            } else {
-                self.up = Some(0)
-            }
-        } else {
-            self.up = None;
-        }
-        if keys.contains(KeyPad::DPAD_DOWN) {
-            if let Some(c) = &mut self.down {
-                *c += 1;
-            } else {
-                self.down = Some(0)
-            }
-        } else {
-            self.down = None;
-        }
-        if keys.contains(KeyPad::DPAD_LEFT) {
-            if let Some(c) = &mut self.left {
-                *c += 1;
-            } else {
-                self.left = Some(0)
-            }
-        } else {
-            self.left = None;
-        }
-        if keys.contains(KeyPad::DPAD_RIGHT) {
-            if let Some(c) = &mut self.right {
-                *c += 1;
-            } else {
-                self.right = Some(0)
-            }
-        } else {
-            self.right = None;
-        }
-    }
-
-    pub fn should_click(&self, key: KeyPad) -> bool {
-        let check = |t| t == 0 || (t >= Self::FIRST_PRESS_TIME && t % Self::LOOP_PRESS_TIME == 0);
-
-        if key == KeyPad::DPAD_UP {
-            if let Some(t) = self.up {
-                check(t)
-            } else {
-                false
-            }
-        } else if key == KeyPad::DPAD_DOWN {
-            if let Some(t) = self.down {
-                check(t)
-            } else {
-                false
-            }
-        } else if key == KeyPad::DPAD_LEFT {
-            if let Some(t) = self.left {
-                check(t)
-            } else {
-                false
-            }
-        } else if key == KeyPad::DPAD_RIGHT {
-            if let Some(t) = self.right {
-                check(t)
-            } else {
-                false
-            }
-        } else {
-            false
-        }
-    }
+    pub touch_controller: TouchController,
+    /// Submenu and cursor to go back to if an unsaved changes prompt is cancelled
+    pub return_to: (SubMenu, u32),
+    /// What the unsaved changes prompt interrupted, carried on with once the changes are
+    /// saved or discarded
+    pub resume: Option<MenuAction>,
+    /// What the log viewer is showing
+    pub log_filter: log::Filter,
+    /// Where the log was last saved from the log viewer
+    pub log_saved: Option<String>,
+    /// What went wrong with the last thing tried, shown until the menu changes
+    pub error: Option<String>,
 }
 
 #[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum SubMenu {
     Main,
     Run,
+    RunVanilla,
+    Conflicts,
+    Saltwater,
     Options,
+    /// Mod profile and plugin for each version, part of Settings
+    LaunchOptions,
     #[cfg(feature = "audio")]
     Music,
     SetUp(bool),
     Credits,
-    #[cfg(debug_assertions)]
+    Unsaved(Unsaved),
     Log,
 }
 
+/// What was being left when the unsaved changes prompt showed up
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum Unsaved {
+    /// Set up mods, with changes to saltwater.cfg
+    Config,
+    /// Settings, with changes to cfg.toml
+    Settings,
+    /// Barista itself, with changes to either
+    Exit,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MenuAction {
     // All
@@ -119,23 +72,56 @@ pub enum MenuAction {
     ChangeMenu(SubMenu),
     Exit,
     UpdateScreen,
+    /// Go back to the main menu, asking first if there's anything unsaved
+    Leave,
+
+    // Unsaved changes prompt
+    SaveChanges,
+    DiscardChanges,
+    KeepEditing,
+    SaveAndExit,
 
     // Run
     Run,
+    RunVanilla,
+
+    // Conflicts
+    /// Disable or re-enable the highlighted conflict for this session
+    ToggleConflict,
+
+    // Saltwater
+    /// Install the highlighted update
+    InstallSaltwater,
+    /// Go back to the Saltwater that was installed before
+    RollbackSaltwater,
 
     // Options
-    ToggleSetting(u8),
+    ToggleSetting(Setting),
     SaveSettings,
 
+    // LaunchOptions
+    /// Move the highlighted profile or plugin to its next choice
+    CycleLaunchOption,
+
     // SetUp
     ChangePage(bool),
     SaveConfig,
     ToggleMod,
     ChangeIndex(bool, bool),
+    Undo,
+    /// Switch to the next mod profile
+    ChangeProfile,
 
     // Music
     #[cfg(feature = "audio")]
     ToggleAudio,
+
+    // Log
+    /// Show more (true) or fewer log levels
+    LogLevel(bool),
+    /// Show the next category of log messages
+    LogCategory,
+    SaveLog,
 }
 
 impl Default for MenuState {
@@ -145,70 +131,56 @@ impl Default for MenuState {
             cursor: 0,
             action: MenuAction::None,
             hold_controller: HoldController::default(),
+            touch_controller: TouchController::default(),
+            return_to: (SubMenu::Main, 0),
+            resume: None,
+            log_filter: log::Filter::default(),
+            log_saved: None,
+            error: None,
         }
     }
 }
 
-impl SubMenu {
-    const ACTIONS_MAIN: &'static [MenuAction] = &[
-        MenuAction::ChangeMenu(SubMenu::Run),
-        MenuAction::ChangeMenu(SubMenu::SetUp(false)),
-        #[cfg(feature = "audio")]
-        MenuAction::ChangeMenu(SubMenu::Music),
-        MenuAction::ChangeMenu(SubMenu::Options),
-        MenuAction::ChangeMenu(SubMenu::Credits),
-        MenuAction::Exit,
-    ];
-    const ACTIONS_RUN: [MenuAction; 1] = [MenuAction::ChangeMenu(SubMenu::Main)];
-    const ACTIONS_SETUP: [MenuAction; 3] = [
-        MenuAction::ChangePage(false),
-        MenuAction::ChangePage(true),
-        MenuAction::SaveConfig,
-    ];
-    #[cfg(feature = "audio")]
-    const ACTIONS_MUSIC: [MenuAction; 2] = [
-        MenuAction::ToggleAudio,
-        MenuAction::ChangeMenu(SubMenu::Main),
-    ];
-    const ACTIONS_OPTIONS: [MenuAction; 5] = [
-        MenuAction::ToggleSetting(0),
-        MenuAction::ToggleSetting(1),
-        MenuAction::ToggleSetting(2),
-        MenuAction::ToggleSetting(3),
-        MenuAction::SaveSettings,
-    ];
-
-    pub fn actions(&self) -> &[MenuAction] {
-        match &self {
-            SubMenu::Main => Self::ACTIONS_MAIN,
-            SubMenu::Run => &Self::ACTIONS_RUN,
-            SubMenu::SetUp(_) => &Self::ACTIONS_SETUP,
-            #[cfg(feature = "audio")]
-            SubMenu::Music => &Self::ACTIONS_MUSIC,
-            SubMenu::Options => &Self::ACTIONS_OPTIONS,
-            SubMenu::Credits => &[MenuAction::ChangeMenu(SubMenu::Main)],
-            #[cfg(debug_assertions)]
-            SubMenu::Log => &[MenuAction::ChangeMenu(SubMenu::Main)],
+impl MenuState {
+    /// How many entries the runtime list of the current submenu has
+    pub fn list_len(
+        &self,
+        versions: &[GameVer],
+        mods: &[(String, u16)],
+        conflicts: &[Conflict],
+        updates: &[Update],
+    ) -> usize {
+        match self.sub_menu {
+            SubMenu::Run | SubMenu::RunVanilla => versions.len(),
+            SubMenu::Conflicts => conflicts.len(),
+            SubMenu::Saltwater => updates.len(),
+            SubMenu::Log => log::lines(&self.log_filter).len(),
+            SubMenu::SetUp(_) => mods.len(),
+            // A profile and a plugin row for each version
+            SubMenu::LaunchOptions => versions.len() * 2,
+            _ => 0,
         }
     }
 
-    pub fn cursor_option_len(&self, versions: &[GameVer], mods: &[(String, u16)]) -> u32 {
-        (self.actions().len()
-            + if let SubMenu::Run = self {
-                versions.len()
-            } else if let SubMenu::SetUp(_) = self {
-                mods.len()
-            } else {
-                0
-            }) as u32
+    pub fn menu(
+        &self,
+        versions: &[GameVer],
+        mods: &[(String, u16)],
+        conflicts: &[Conflict],
+        updates: &[Update],
+    ) -> &'static Menu {
+        self.sub_menu
+            .menu(self.list_len(versions, mods, conflicts, updates))
     }
-}
 
-impl MenuState {
-    pub fn actions(&self) -> &[MenuAction] {
-        self.sub_menu.actions()
-    }
-    pub fn cursor_option_len(&self, versions: &[GameVer], mods: &[(String, u16)]) -> u32 {
-        self.sub_menu.cursor_option_len(versions, mods)
+    pub fn cursor_option_len(
+        &self,
+        versions: &[GameVer],
+        mods: &[(String, u16)],
+        conflicts: &[Conflict],
+        updates: &[Update],
+    ) -> u32 {
+        let list_len = self.list_len(versions, mods, conflicts, updates);
+        self.sub_menu.menu(list_len).len(list_len) as u32
     }
 }
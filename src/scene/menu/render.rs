@@ -1,294 +1,340 @@
-use ctru::{console::Console, services::ps::Ps};
+use ctru::services::ps::Ps;
 
 use crate::{
+    app::App,
     constants::{
         SLOT_NAMES_DEFAULT, SLOT_NAMES_GATE, SLOT_NAMES_INFERNAL, SLOT_NAMES_INFERNAL_GATE,
         SLOT_NAMES_INTERNAL, SLOT_NAMES_INTERNAL_GATE, SLOT_NAMES_NORETCON,
     },
-    format::barista_cfg::{BaristaConfig, SlotTitleMode},
-    launcher::GameVer,
-    Result,
+    format::barista_cfg::SlotTitleMode,
+    i18n, mod_picker, Result,
 };
 
-use super::{MenuState, SubMenu};
+use super::{
+    backend::MenuRenderer,
+    tree::{Entry, ItemKind, ListKind, MenuItem},
+    MenuState, SubMenu,
+};
+
+/// Everything the bottom screen needs to show the current menu
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MenuView {
+    pub title: String,
+    pub header: String,
+    pub items: Vec<String>,
+    /// Highlighted item, if the menu has a cursor at all
+    pub cursor: Option<usize>,
+    pub footer: String,
+    pub back_button: bool,
+    pub save_button: bool,
+    pub undo_button: bool,
+    pub page_buttons: bool,
+}
 
 impl MenuState {
+    /// Shows the current menu, `mods` being the current page of Set up mods
     pub fn render(
         &mut self,
-        console: &Console,
-        versions: &[GameVer],
+        app: &mut App<impl MenuRenderer>,
         mods: &[(String, u16)],
-        page: usize,
-        num_pages: usize,
-        settings: &BaristaConfig,
     ) -> Result<()> {
-        console.clear();
+        let view = self.view(app, mods)?;
+        app.ui.show(&view);
+        Ok(())
+    }
+
+    pub fn view<R>(&self, app: &App<R>, mods: &[(String, u16)]) -> Result<MenuView> {
+        let App {
+            settings,
+            versions,
+            page,
+            conflicts,
+            saltwater,
+            ..
+        } = app;
+        let num_pages = mod_picker::num_pages(&app.mods);
+
+        let menu = self.menu(versions, mods, conflicts, &saltwater.updates);
+        let mut view = MenuView {
+            title: tr!("text_menu.title") + &i18n::tr(menu.title),
+            cursor: Some(self.cursor as usize),
+            back_button: self.sub_menu != SubMenu::Main,
+            ..Default::default()
+        };
+
+        match menu.list {
+            ListKind::None => {}
+            ListKind::Versions => {
+                view.items = versions
+                    .iter()
+                    .map(|c| {
+                        if c.is_supported() {
+                            c.to_string()
+                        } else {
+                            format!("{} (!)", c)
+                        }
+                    })
+                    .collect()
+            }
+            ListKind::VanillaVersions => {
+                view.items = versions.iter().map(|c| c.to_string()).collect()
+            }
+            ListKind::Conflicts => view.items = conflicts.iter().map(|c| c.label()).collect(),
+            ListKind::SaltwaterUpdates => {
+                view.items = saltwater.updates.iter().map(|c| c.to_string()).collect()
+            }
+            ListKind::Log => view.items = crate::log::lines(&self.log_filter),
+            ListKind::LaunchOptions => {
+                let default = tr!("launch_options.default");
+                for ver in versions {
+                    let options = settings.versions.get(&ver.key());
+                    let profile = options.and_then(|c| c.profile.as_deref());
+                    // Just the file name, the folder is always the same
+                    let plugin = options
+                        .and_then(|c| c.plugin.as_deref())
+                        .map(|c| c.rsplit('/').next().unwrap_or(c));
+                    view.items.push(tr!(
+                        "launch_options.profile",
+                        ver.key(),
+                        profile.unwrap_or(&default)
+                    ));
+                    view.items.push(tr!(
+                        "launch_options.plugin",
+                        ver.key(),
+                        plugin.unwrap_or(&default)
+                    ));
+                }
+            }
+            ListKind::Mods => {
+                let show_slots = self.sub_menu == SubMenu::SetUp(true);
+                let slot_not_found = tr!("setup.slot_not_found");
+                let slot_not_found = slot_not_found.as_str();
+                for elmt in mods {
+                    view.items.push(format!(
+                        "{} {}",
+                        match elmt.1 {
+                            u16::MAX => "---".to_string(),
+                            c =>
+                                if c >= 0x100 && !settings.original_gates {
+                                    format!(
+                                        "G{}{}",
+                                        if c >= 0x110 { c & 3 } else { (c & 0xFF) >> 2 },
+                                        if c >= 0x110 {
+                                            "P".to_string()
+                                        } else if c & 3 == 3 {
+                                            "E".to_string()
+                                        } else {
+                                            (c & 3).to_string()
+                                        }
+                                    )
+                                } else {
+                                    format!("{:03X}", c)
+                                },
+                        },
+                        // TODO: slot mode
+                        if !show_slots || elmt.1 == u16::MAX {
+                            elmt.0.clone()
+                        } else if elmt.1 >= 0x100 {
+                            String::from("->")
+                                + *match settings.slot_titles {
+                                    SlotTitleMode::Internal => SLOT_NAMES_INTERNAL_GATE,
+                                    SlotTitleMode::Megamix | SlotTitleMode::Original => {
+                                        SLOT_NAMES_GATE
+                                    }
+                                    SlotTitleMode::Infernal => SLOT_NAMES_INFERNAL_GATE,
+                                }
+                                .get((elmt.1 - 0x100) as usize)
+                                .unwrap_or(&slot_not_found)
+                        } else {
+                            let letters;
+                            String::from("->")
+                                + if settings.slot_titles == SlotTitleMode::Infernal
+                                    && elmt.1 == 0x58
+                                {
+                                    letters = generate_random_letters::<10>()?;
+                                    &letters
+                                } else {
+                                    *match settings.slot_titles {
+                                        SlotTitleMode::Internal => SLOT_NAMES_INTERNAL,
+                                        SlotTitleMode::Megamix => SLOT_NAMES_DEFAULT,
+                                        SlotTitleMode::Original => SLOT_NAMES_NORETCON,
+                                        SlotTitleMode::Infernal => SLOT_NAMES_INFERNAL,
+                                    }
+                                    .get(elmt.1 as usize)
+                                    .unwrap_or(&slot_not_found)
+                                }
+                        }
+                    ));
+                }
+            }
+        }
+        view.items
+            .extend(menu.items.iter().map(|c| c.label(settings)));
+
         match &self.sub_menu {
             SubMenu::Main => {
-                println!("Barista - Main menu");
-                println!();
-                println!("Controls:");
-                println!("- DPad up/down: move cursor");
-                println!("- A: choose selected option");
-                println!("- B: go to prev menu");
-                println!("- Start: exit Barista");
+                view.header = tr!("text_menu.controls");
+                if saltwater.installed.is_none() {
+                    view.header += "\n";
+                    view.header += &tr!("saltwater.missing_warning");
+                }
                 #[cfg(debug_assertions)]
-                println!("- Select: open debug log");
-                println!();
-                println!(
-                    " [{}] Run Saltwater",
-                    if self.cursor == 0 { "*" } else { " " }
-                );
-                println!(
-                    " [{}] Set up mods",
-                    if self.cursor == 1 { "*" } else { " " }
-                );
-                #[cfg(feature = "audio")]
-                println!(" [{}] Music", if self.cursor == 2 { "*" } else { " " });
-
-                let cursor_increase = if cfg!(feature = "audio") { 1 } else { 0 };
-
-                println!(
-                    " [{}] Settings",
-                    if self.cursor == 2 + cursor_increase {
-                        "*"
-                    } else {
-                        " "
-                    }
-                );
-                println!(
-                    " [{}] Credits",
-                    if self.cursor == 3 + cursor_increase {
-                        "*"
-                    } else {
-                        " "
-                    }
-                );
-                println!(
-                    " [{}] Exit Barista",
-                    if self.cursor == 4 + cursor_increase {
-                        "*"
-                    } else {
-                        " "
+                {
+                    view.header += "\n";
+                    view.header += &tr!("text_menu.controls_debug");
+                    view.footer = tr!("text_menu.debug_commit", env!("GIT_HASH"));
+                }
+            }
+            SubMenu::Run | SubMenu::RunVanilla => {
+                view.header = if self.sub_menu == SubMenu::Run {
+                    tr!("text_menu.choose_ver")
+                } else {
+                    tr!("text_menu.choose_ver_vanilla")
+                };
+                if let Some(c) = versions.get(self.cursor as usize) {
+                    view.header += "\n";
+                    view.header += &c.version_info();
+                    if self.sub_menu == SubMenu::Run && !c.is_supported() {
+                        view.header += "\n";
+                        view.header += &tr!("launcher.unsupported");
                     }
-                );
-                #[cfg(debug_assertions)]
-                println!("\x1b[29;0HBarista debug commit {}", env!("GIT_HASH"));
+                }
+                if self.sub_menu == SubMenu::Run
+                    && saltwater.loader.as_ref().is_some_and(|c| !c.enabled)
+                {
+                    view.header += "\n";
+                    view.header += &tr!("launcher.plgldr_off");
+                }
+            }
+            SubMenu::Conflicts => {
+                view.header = if conflicts.is_empty() {
+                    tr!("conflicts.none")
+                } else {
+                    tr!("conflicts.header")
+                }
             }
-            SubMenu::Run => {
-                println!("Barista - Run Saltwater");
-                println!();
-                println!("Choose a version to run with Saltwater");
-                println!();
-                for (vnum, ver) in versions.iter().enumerate() {
-                    println!(
-                        " [{}] {}",
-                        if self.cursor as usize == vnum {
-                            "*"
+            SubMenu::Saltwater => {
+                let installed = match &saltwater.installed {
+                    Some(c) => tr!("saltwater.installed", c),
+                    None => tr!("saltwater.not_installed"),
+                };
+                let backup = match &saltwater.backup {
+                    Some(c) => tr!("saltwater.backup", c.version),
+                    None => tr!("saltwater.no_backup"),
+                };
+                let updates = if saltwater.updates.is_empty() {
+                    tr!("saltwater.no_updates")
+                } else {
+                    tr!("saltwater.choose_update")
+                };
+                let loader = match &saltwater.loader {
+                    Some(c) => tr!(
+                        "saltwater.loader",
+                        c.version,
+                        if c.enabled {
+                            tr!("settings.on")
                         } else {
-                            " "
+                            tr!("settings.off")
                         },
-                        ver
-                    );
+                        c.arena_size / 1024
+                    ),
+                    None => tr!("saltwater.no_loader"),
+                };
+                let mut lines = vec![installed, backup, loader];
+                if let Some(c) = saltwater
+                    .loader
+                    .as_ref()
+                    .and_then(|c| c.last_plugin.as_ref())
+                {
+                    lines.push(tr!("saltwater.last_plugin", c));
                 }
-                println!(
-                    " [{}] Back",
-                    if self.cursor as usize == versions.len() {
-                        "*"
-                    } else {
-                        " "
-                    }
-                );
+                lines.push(updates);
+                view.header = lines.join("\n");
             }
-            SubMenu::SetUp(c) => {
-                println!("Barista - Set up mods");
-                println!();
+            SubMenu::SetUp(_) => {
                 if mods.is_empty() {
-                    println!(
-                        "Put some mods in your /spicerack/mods\nfolder in order to load them!"
-                    );
-                    println!();
-                    println!("- [*] Back")
+                    view.header = tr!("setup.no_mods");
                 } else {
-                    println!("Choose what mods to load with Saltwater");
-                    println!("Disabled mods show index --- instead");
-                    println!();
-                    println!("A to enable/disable mods");
-                    println!("DPad Left/Right to change index");
-                    println!("Hold X to scroll indexes faster");
-                    println!("L/R change page, Y shows slot names");
-                    println!();
-                    println!("Page {} of {}", page + 1, num_pages);
-                    for (i, elmt) in mods.iter().enumerate() {
-                        println!(
-                            "- [{}] {} {}",
-                            if self.cursor == i as u32 { "*" } else { " " },
-                            match elmt.1 {
-                                u16::MAX => "---".to_string(),
-                                c =>
-                                    if c >= 0x100 && !settings.original_gates {
-                                        format!(
-                                            "G{}{}",
-                                            if c >= 0x110 { c & 3 } else { (c & 0xFF) >> 2 },
-                                            if c >= 0x110 {
-                                                "P".to_string()
-                                            } else if c & 3 == 3 {
-                                                "E".to_string()
-                                            } else {
-                                                (c & 3).to_string()
-                                            }
-                                        )
-                                    } else {
-                                        format!("{:03X}", c)
-                                    },
-                            },
-                            // TODO: slot mode
-                            if !*c || elmt.1 == u16::MAX {
-                                elmt.0.clone()
-                            } else if elmt.1 >= 0x100 {
-                                String::from("->")
-                                    + *match settings.slot_titles {
-                                        SlotTitleMode::Internal => SLOT_NAMES_INTERNAL_GATE,
-                                        SlotTitleMode::Megamix | SlotTitleMode::Original => {
-                                            SLOT_NAMES_GATE
-                                        }
-                                        SlotTitleMode::Infernal => SLOT_NAMES_INFERNAL_GATE,
-                                    }
-                                    .get((elmt.1 - 0x100) as usize)
-                                    .unwrap_or(&"slot not found")
-                            } else {
-                                let letters;
-                                String::from("->")
-                                    + if settings.slot_titles == SlotTitleMode::Infernal
-                                        && elmt.1 == 0x58
-                                    {
-                                        letters = generate_random_letters::<10>()?;
-                                        &letters
-                                    } else {
-                                        *match settings.slot_titles {
-                                            SlotTitleMode::Internal => SLOT_NAMES_INTERNAL,
-                                            SlotTitleMode::Megamix => SLOT_NAMES_DEFAULT,
-                                            SlotTitleMode::Original => SLOT_NAMES_NORETCON,
-                                            SlotTitleMode::Infernal => SLOT_NAMES_INFERNAL,
-                                        }
-                                        .get(elmt.1 as usize)
-                                        .unwrap_or(&"slot not found")
-                                    }
-                            }
-                        );
-                    }
-                    println!();
-                    println!(
-                        "- [{}] Previous page",
-                        if self.cursor == self.cursor_option_len(versions, mods) - 3 {
-                            "*"
-                        } else {
-                            " "
-                        }
-                    );
-                    println!(
-                        "- [{}] Next page",
-                        if self.cursor == self.cursor_option_len(versions, mods) - 2 {
-                            "*"
-                        } else {
-                            " "
-                        }
-                    );
-                    println!();
-                    println!(
-                        "- [{}] Back",
-                        if self.cursor == self.cursor_option_len(versions, mods) - 1 {
-                            "*"
-                        } else {
-                            " "
-                        }
-                    );
+                    view.save_button = true;
+                    view.undo_button = true;
+                    view.page_buttons = true;
+                    view.header = [
+                        tr!("setup.choose_mods"),
+                        tr!("setup.disabled_hint"),
+                        tr!("setup.controls"),
+                    ]
+                    .join("\n");
+                    let profile = match &app.profile {
+                        Some(c) => c.clone(),
+                        None => tr!("setup.profile_default"),
+                    };
+                    view.footer = tr!("setup.page", page + 1, num_pages)
+                        + "  "
+                        + &tr!("setup.profile", profile);
                 }
             }
             #[cfg(feature = "audio")]
-            SubMenu::Music => {
-                println!("Barista - Music");
-                println!();
-                println!("Current status: very broken");
-                println!();
-                println!(
-                    " [{}] {}",
-                    if self.cursor == 0 { "*" } else { " " },
-                    if crate::audio().is_playing() {
-                        "Disable"
-                    } else {
-                        "Enable"
-                    }
-                );
-                println!(" [{}] Back", if self.cursor == 1 { "*" } else { " " })
-            }
+            SubMenu::Music => view.header = tr!("music.status"),
             SubMenu::Options => {
-                println!("Barista - Settings");
-                println!();
-                println!(
-                    " [{}] Use 0x100 format for gates: {}",
-                    if self.cursor == 0 { "*" } else { " " },
-                    if settings.original_gates { "on" } else { "off" }
-                );
-                println!(
-                    " [{}] Slot title mode: {}",
-                    if self.cursor == 1 { "*" } else { " " },
-                    match settings.slot_titles {
-                        SlotTitleMode::Megamix => "Megamix",
-                        SlotTitleMode::Original => "Original",
-                        SlotTitleMode::Internal => "Internal",
-                        SlotTitleMode::Infernal => "Infernal...?",
-                    }
-                );
-                println!(
-                    " [{}] Display mod loaded msg: {}",
-                    if self.cursor == 2 { "*" } else { " " },
-                    if settings.btk_loaded_msg { "on" } else { "off" }
-                );
-                println!(
-                    " [{}] Enable extra rows: {}",
-                    if self.cursor == 3 { "*" } else { " " },
-                    if settings.extra_rows { "on" } else { "off" }
-                );
-                println!();
-                println!(" [{}] Back", if self.cursor == 4 { "*" } else { " " })
+                view.save_button = true;
+                if let Some(Entry::Item(MenuItem {
+                    kind: ItemKind::Toggle(c) | ItemKind::Cycle(c),
+                    ..
+                })) = menu.entry(self.cursor as usize, 0)
+                {
+                    view.header = i18n::tr(c.description);
+                }
+            }
+            SubMenu::LaunchOptions => {
+                view.save_button = true;
+                view.header = if versions.is_empty() {
+                    tr!("launch_options.no_versions")
+                } else {
+                    tr!("launch_options.header")
+                };
             }
             SubMenu::Credits => {
-                println!("Barista + Saltwater - Credits:");
-                println!();
-                println!("Project lead:");
-                println!("    patataofcourse");
-                println!();
-                println!("Art:");
-                println!("    MilaTheArtsy");
-                println!();
-                println!("Programming, RE & research:");
-                println!("    patataofcourse");
-                println!("    0xAdk");
-                println!("    EstexNT");
-                println!();
-                println!("Alpha testing:");
-                println!("    Kievit");
-                println!("    Nate Candles");
-                println!("    somethingAccurate");
-                println!("    TheAlternateDoctor");
-                println!("    Tox (tox2564)");
-                println!("    Zeo (thatzeogal)");
-                println!();
-                println!("Special thanks to:");
-                println!("    PabloMK7 & Nanquitas");
-                println!("    The CTPG-7 Team");
-                println!("    The Rust 3DS project");
-                println!("    The RHModding community");
-                println!();
-                println!("Press B to return to the main menu");
+                view.header = [
+                    tr!("credits.lead"),
+                    "  patataofcourse".to_string(),
+                    tr!("credits.art"),
+                    "  MilaTheArtsy".to_string(),
+                    tr!("credits.programming"),
+                    "  patataofcourse, 0xAdk, EstexNT".to_string(),
+                    tr!("credits.testing"),
+                    "  Kievit, Nate Candles, somethingAccurate,".to_string(),
+                    "  TheAlternateDoctor, Tox (tox2564),".to_string(),
+                    "  Zeo (thatzeogal)".to_string(),
+                    tr!("credits.thanks"),
+                    "  PabloMK7 & Nanquitas, The CTPG-7 Team,".to_string(),
+                    "  The Rust 3DS project,".to_string(),
+                    "  The RHModding community".to_string(),
+                ]
+                .join("\n");
+                view.footer = tr!("credits.return");
             }
-            #[cfg(debug_assertions)]
+            SubMenu::Unsaved(_) => view.header = tr!("unsaved.message"),
             SubMenu::Log => {
-                println!("{}", crate::log::LOG.lock().unwrap());
+                let category = match self.log_filter.category {
+                    Some(c) => c.to_string(),
+                    None => tr!("log.all"),
+                };
+                view.header = tr!("log.filter", self.log_filter.level, category);
+                view.header += "\n";
+                view.header += &tr!("log.controls");
+                if let Some(c) = &self.log_saved {
+                    view.header += "\n";
+                    view.header += &tr!("log.saved", c);
+                }
             }
         }
-        Ok(())
+        if let Some(c) = &self.error {
+            if !view.header.is_empty() {
+                view.header += "\n";
+            }
+            view.header += c;
+        }
+        Ok(view)
     }
 }
 
@@ -311,6 +357,5 @@ pub fn generate_random_letters<const I: usize>() -> crate::Result<String> {
         }
         break;
     }
-    log!(General, "{:X?}", bytes);
     Ok(String::from_utf8(bytes.to_vec()).unwrap())
 }
@@ -1,126 +1,297 @@
-use std::path::PathBuf;
+use input_lib::TouchEvent;
 
-use ctru::{
-    console::Console,
-    services::hid::{Hid, KeyPad},
+use crate::{
+    app::{self, App, Tracked},
+    format::{barista_cfg::next_choice, saltwater_cfg::Config},
+    i18n,
+    input::{Binding, Keys},
+    log, mod_picker, saltwater, Result,
 };
 
-use crate::{
-    format::barista_cfg::{BaristaConfig, SlotTitleMode},
-    launcher::GameVer,
-    mod_picker, Result,
+use super::{
+    backend::{MenuInput, MenuRenderer, TouchTarget},
+    MenuAction, MenuState, SubMenu, Unsaved,
 };
 
-use super::{MenuAction, MenuState, SubMenu};
+/// What the stylus did this frame, in terms of menu controls
+#[derive(Default)]
+struct TouchInput {
+    select: Option<u32>,
+    confirm: bool,
+    back: bool,
+    save: bool,
+    undo: bool,
+    page: Option<bool>,
+}
 
 impl MenuState {
-    pub fn run(
+    fn touch_input(
         &mut self,
-        hid: &Hid,
-        console: &Console,
-        versions: &[GameVer],
-        mods: &[PathBuf],
-        page: &mut usize,
-        settings: &mut BaristaConfig,
-    ) -> Result<()> {
+        input: &impl MenuInput,
+        renderer: &mut impl MenuRenderer,
+    ) -> TouchInput {
+        let mut out = TouchInput::default();
+        let event = self.touch_controller.update(
+            input.keys_held().contains(Keys::TOUCH),
+            input.touch_position(),
+        );
+
+        match event {
+            Some(TouchEvent::Drag(dy)) => renderer.drag_list(dy),
+            Some(TouchEvent::Tap(x, y)) => match renderer.touched(x, y) {
+                // Tapping an entry selects it, tapping it again activates it
+                Some(TouchTarget::Row(c)) if c as u32 == self.cursor => out.confirm = true,
+                Some(TouchTarget::Row(c)) => out.select = Some(c as u32),
+                Some(TouchTarget::Back) => out.back = true,
+                Some(TouchTarget::Save) => out.save = true,
+                Some(TouchTarget::Undo) => out.undo = true,
+                Some(TouchTarget::PrevPage) => out.page = Some(false),
+                Some(TouchTarget::NextPage) => out.page = Some(true),
+                None => {}
+            },
+            None => {}
+        }
+        out
+    }
+
+    pub fn run(&mut self, input: &impl MenuInput, app: &mut App<impl MenuRenderer>) -> Result<()> {
         self.action = MenuAction::None;
 
+        let App {
+            ui: renderer,
+            settings,
+            config,
+            mod_undo,
+            profile,
+            profiles: found_profiles,
+            versions,
+            mods,
+            page,
+            conflicts,
+            saltwater: saltwater_status,
+            ..
+        } = app;
+        let updates = &saltwater_status.updates;
+
         let mut mod_page = if let SubMenu::SetUp(_) = self.sub_menu {
-            mod_picker::show_page(mods, crate::config(), *page)
+            mod_picker::show_page(mods, config, *page)
         } else {
             vec![]
         };
 
-        if hid.keys_down().contains(KeyPad::START) {
-            self.action = MenuAction::Exit;
-            return Ok(());
-        }
+        let bindings = settings.bindings.clone();
+        let pressed = |c| bindings.matches(c, input.keys_down());
+        let held = |c| bindings.matches(c, input.keys_held());
 
-        self.hold_controller.update(hid.keys_held());
+        self.hold_controller.update(input.keys_held(), &bindings);
+        let touch = self.touch_input(input, renderer);
 
-        if self.hold_controller.should_click(KeyPad::DPAD_UP) {
+        if let Some(c) = touch.select {
+            self.cursor = c;
+            self.action = MenuAction::UpdateScreen
+        } else if self.hold_controller.should_click(Binding::Up) {
             if self.cursor > 0 {
                 self.cursor -= 1;
             } else {
-                self.cursor = self.cursor_option_len(versions, &mod_page) - 1;
+                self.cursor = self.cursor_option_len(versions, &mod_page, conflicts, updates) - 1;
             }
             self.action = MenuAction::UpdateScreen
-        } else if self.hold_controller.should_click(KeyPad::DPAD_DOWN) {
-            if self.cursor < self.cursor_option_len(versions, &mod_page) - 1 {
+        } else if self.hold_controller.should_click(Binding::Down) {
+            if self.cursor < self.cursor_option_len(versions, &mod_page, conflicts, updates) - 1 {
                 self.cursor += 1;
             } else {
                 self.cursor = 0;
             }
             self.action = MenuAction::UpdateScreen
-        } else if hid.keys_down().contains(KeyPad::B) {
-            if let SubMenu::Main = self.sub_menu {
-                self.action = MenuAction::Exit;
-            } else {
-                self.action = self.actions()[self.actions().len() - 1].clone();
-            }
-        } else if hid.keys_down().contains(KeyPad::A) {
-            if let SubMenu::Run = self.sub_menu {
-                if self.cursor == self.cursor_option_len(versions, &mod_page) - 1 {
-                    self.action = MenuAction::ChangeMenu(SubMenu::Main)
-                } else {
-                    self.action = MenuAction::Run
-                }
-            } else if let SubMenu::SetUp(_) = self.sub_menu {
-                if mods.is_empty() {
-                    self.action = SubMenu::ACTIONS_SETUP.last().unwrap().clone();
-                } else if self.cursor_option_len(versions, &mod_page) - self.cursor <= 3 {
-                    self.action =
-                        SubMenu::ACTIONS_SETUP[self.cursor as usize - mod_page.len()].clone();
-                } else {
-                    self.action = MenuAction::ToggleMod;
-                }
-            } else {
-                self.action = self.actions()[self.cursor as usize].clone()
+        } else if pressed(Binding::Back) || touch.back {
+            self.action = self
+                .menu(versions, &mod_page, conflicts, updates)
+                .back
+                .clone();
+        } else if touch.save {
+            match self.sub_menu {
+                SubMenu::SetUp(_) => self.action = MenuAction::SaveConfig,
+                SubMenu::Options | SubMenu::LaunchOptions => self.action = MenuAction::SaveSettings,
+                _ => {}
             }
+        } else if pressed(Binding::Confirm) || touch.confirm {
+            let list_len = self.list_len(versions, &mod_page, conflicts, updates);
+            self.action = self
+                .menu(versions, &mod_page, conflicts, updates)
+                .action(self.cursor as usize, list_len);
         }
-        #[cfg(debug_assertions)]
-        if hid.keys_down().contains(KeyPad::SELECT) {
+        if pressed(Binding::Log) && bindings.matches_all(Binding::LogHold, input.keys_held()) {
             self.action = MenuAction::ChangeMenu(SubMenu::Log)
         }
+        if self.sub_menu == SubMenu::Log {
+            if self.hold_controller.should_click(Binding::Left) {
+                self.action = MenuAction::LogLevel(false)
+            } else if self.hold_controller.should_click(Binding::Right) {
+                self.action = MenuAction::LogLevel(true)
+            } else if pressed(Binding::SlotNames) {
+                self.action = MenuAction::LogCategory
+            }
+        }
         if let SubMenu::SetUp(c) = &mut self.sub_menu {
-            if hid.keys_down().contains(KeyPad::Y) {
+            if pressed(Binding::SlotNames) {
                 *c = !*c;
                 self.action = MenuAction::UpdateScreen
             }
-            if hid.keys_down().contains(KeyPad::L) {
+            if pressed(Binding::Undo) || touch.undo {
+                self.action = MenuAction::Undo
+            } else if pressed(Binding::Profile) {
+                self.action = MenuAction::ChangeProfile
+            } else if let Some(c) = touch.page {
+                self.action = MenuAction::ChangePage(c)
+            } else if pressed(Binding::PrevPage) {
                 self.action = MenuAction::ChangePage(false)
-            } else if hid.keys_down().contains(KeyPad::R) {
+            } else if pressed(Binding::NextPage) {
                 self.action = MenuAction::ChangePage(true)
-            } else if self.hold_controller.should_click(KeyPad::DPAD_LEFT) {
-                if hid.keys_held().contains(KeyPad::X) {
+            } else if self.hold_controller.should_click(Binding::Left) {
+                if held(Binding::FastScroll) {
                     self.action = MenuAction::ChangeIndex(false, true)
                 } else {
                     self.action = MenuAction::ChangeIndex(false, false)
                 }
-            } else if self.hold_controller.should_click(KeyPad::DPAD_RIGHT) {
-                if hid.keys_held().contains(KeyPad::X) {
+            } else if self.hold_controller.should_click(Binding::Right) {
+                if held(Binding::FastScroll) {
                     self.action = MenuAction::ChangeIndex(true, true)
                 } else {
                     self.action = MenuAction::ChangeIndex(true, false)
                 }
             }
         }
+        if pressed(Binding::Exit) {
+            self.action = MenuAction::Exit;
+        }
+
+        // Back from the prompt with the changes saved or discarded, so carry on with whatever
+        // opened it. This waits a frame so main has saved by the time it happens
+        if !matches!(self.sub_menu, SubMenu::Unsaved(_)) {
+            if let Some(c) = self.resume.take() {
+                self.action = c;
+            }
+        }
+
+        // Leaving with unsaved changes asks what to do with them first
+        if let SubMenu::Unsaved(kind) = self.sub_menu {
+            // There's nowhere further to go from the exit prompt, so exiting again backs out
+            if (&self.action, kind) == (&MenuAction::Exit, Unsaved::Exit) {
+                self.action = MenuAction::KeepEditing;
+            }
+            self.action = match (&self.action, kind) {
+                (MenuAction::SaveChanges, Unsaved::Config) => MenuAction::SaveConfig,
+                (MenuAction::SaveChanges, Unsaved::Settings) => MenuAction::SaveSettings,
+                (MenuAction::SaveChanges, Unsaved::Exit) => MenuAction::SaveAndExit,
+                (MenuAction::DiscardChanges, Unsaved::Config) => {
+                    config.discard();
+                    mod_undo.clear();
+                    (self.sub_menu, self.cursor) = self.return_to;
+                    mod_page = mod_picker::show_page(mods, config, *page);
+                    MenuAction::UpdateScreen
+                }
+                (MenuAction::DiscardChanges, Unsaved::Settings) => {
+                    settings.discard();
+                    i18n::set_language(settings);
+                    (self.sub_menu, self.cursor) = self.return_to;
+                    MenuAction::UpdateScreen
+                }
+                (MenuAction::DiscardChanges, Unsaved::Exit) => MenuAction::Exit,
+                (MenuAction::KeepEditing, _) => {
+                    self.resume = None;
+                    (self.sub_menu, self.cursor) = self.return_to;
+                    if let SubMenu::SetUp(_) = self.sub_menu {
+                        mod_page = mod_picker::show_page(mods, config, *page);
+                    }
+                    MenuAction::UpdateScreen
+                }
+                // Exiting from the other prompts keeps asking, now about everything
+                (MenuAction::Exit, _) => {
+                    self.resume = None;
+                    self.sub_menu = SubMenu::Unsaved(Unsaved::Exit);
+                    MenuAction::UpdateScreen
+                }
+                (c, _) => c.clone(),
+            };
+        } else {
+            let unsaved = match self.action {
+                MenuAction::Exit if settings.is_dirty() || config.is_dirty() => Some(Unsaved::Exit),
+                MenuAction::Leave | MenuAction::ChangeProfile | MenuAction::ChangeMenu(_) => {
+                    match self.sub_menu {
+                        SubMenu::SetUp(_) if config.is_dirty() => Some(Unsaved::Config),
+                        // Both halves of Settings are saved together, so going between them
+                        // doesn't need asking
+                        SubMenu::Options | SubMenu::LaunchOptions
+                            if settings.is_dirty()
+                                && !matches!(
+                                    self.action,
+                                    MenuAction::ChangeMenu(
+                                        SubMenu::Options | SubMenu::LaunchOptions
+                                    )
+                                ) =>
+                        {
+                            Some(Unsaved::Settings)
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(c) = unsaved {
+                if c != Unsaved::Exit {
+                    self.resume = Some(self.action.clone());
+                }
+                self.return_to = (self.sub_menu, self.cursor);
+                self.sub_menu = SubMenu::Unsaved(c);
+                self.cursor = 0;
+                self.action = MenuAction::UpdateScreen;
+            } else if self.action == MenuAction::Leave {
+                mod_undo.clear();
+                self.action = MenuAction::ChangeMenu(SubMenu::Main);
+            }
+        }
 
         match &self.action {
-            MenuAction::Exit | MenuAction::Run | MenuAction::None => return Ok(()),
+            MenuAction::Exit
+            | MenuAction::SaveAndExit
+            | MenuAction::Run
+            | MenuAction::RunVanilla
+            | MenuAction::None => return Ok(()),
             MenuAction::ChangeMenu(c) => {
                 if let SubMenu::SetUp(_) = *c {
-                    mod_page = mod_picker::show_page(mods, crate::config(), *page);
+                    mod_page = mod_picker::show_page(mods, config, *page);
                 }
 
                 self.sub_menu = *c;
                 self.cursor = 0;
                 *page = 0;
+                self.error = None;
+
+                if *c == SubMenu::Log {
+                    // Start on the most recent line
+                    self.log_saved = None;
+                    self.cursor = log::lines(&self.log_filter).len().saturating_sub(1) as u32;
+                }
+                if let SubMenu::Run | SubMenu::RunVanilla = c {
+                    // Start on the version that was played last
+                    self.cursor = versions
+                        .iter()
+                        .position(|v| settings.last_launched.as_ref() == Some(&v.key()))
+                        .unwrap_or(0) as u32;
+                }
             }
             MenuAction::SaveConfig | MenuAction::SaveSettings => {
-                self.sub_menu = SubMenu::Main;
-                self.cursor = 0;
-                *page = 0;
+                if self.resume.is_some() {
+                    // Saved from the prompt, go back to where it was opened to carry on
+                    (self.sub_menu, self.cursor) = self.return_to;
+                    if let SubMenu::SetUp(_) = self.sub_menu {
+                        mod_page = mod_picker::show_page(mods, config, *page);
+                    }
+                } else {
+                    self.sub_menu = SubMenu::Main;
+                    self.cursor = 0;
+                    *page = 0;
+                }
             }
             MenuAction::ChangePage(c) => {
                 if !c && *page > 0 {
@@ -129,7 +300,7 @@ impl MenuState {
                     *page += 1;
                 }
                 let old_len = mod_page.len() as u32;
-                mod_page = mod_picker::show_page(mods, crate::config(), *page);
+                mod_page = mod_picker::show_page(mods, config, *page);
 
                 // Make sure the cursor is in-bounds
                 if self.cursor < old_len {
@@ -144,8 +315,13 @@ impl MenuState {
             //TODO: properly order stuff in new gate mode (both ChangeIndex and ToggleMod)
             MenuAction::ChangeIndex(i, fast) => {
                 if let Some(m) = mod_page.get_mut(self.cursor as usize) {
-                    let config = crate::config();
                     if m.1 != u16::MAX {
+                        // Holding the button down counts as a single edit
+                        if !self.hold_controller.is_repeating(Binding::Left)
+                            && !self.hold_controller.is_repeating(Binding::Right)
+                        {
+                            mod_undo.push(&config.btks);
+                        }
                         config.btks.remove(&m.1);
                         let mut step: i16 = if *i { 1 } else { -1 };
                         if *fast {
@@ -174,7 +350,7 @@ impl MenuState {
             }
             MenuAction::ToggleMod => {
                 if let Some(m) = mod_page.get_mut(self.cursor as usize) {
-                    let config = crate::config();
+                    mod_undo.push(&config.btks);
                     if m.1 == u16::MAX {
                         let mut val = 0;
                         while val <= 0x113 && config.btks.contains_key(&val) {
@@ -195,30 +371,129 @@ impl MenuState {
                     }
                 }
             }
-            MenuAction::ToggleSetting(c) => match c {
-                0 => settings.original_gates = !settings.original_gates,
-                1 => {
-                    settings.slot_titles = match settings.slot_titles {
-                        SlotTitleMode::Megamix => SlotTitleMode::Original,
-                        SlotTitleMode::Original => SlotTitleMode::Internal,
-                        SlotTitleMode::Internal | SlotTitleMode::Infernal => SlotTitleMode::Megamix,
+            MenuAction::Undo => {
+                let Some(btks) = mod_undo.pop() else {
+                    // Nothing to undo, don't update the screen
+                    return Ok(());
+                };
+                config.btks = btks;
+                mod_page = mod_picker::show_page(mods, config, *page);
+            }
+            MenuAction::ChangeProfile => {
+                let profiles = app::known_profiles(found_profiles, settings);
+                let next = next_choice(&profiles, profile.as_deref());
+                if next.is_none() && profile.is_none() {
+                    // There's only the default profile
+                    return Ok(());
+                }
+
+                // A profile that can't be read stays out of the way of the one being edited
+                match Config::from_file_or_default(app::config_path(next.as_deref())) {
+                    Ok(mut new_config) => {
+                        *profile = next;
+                        new_config.clear_deleted_mods(mods);
+                        *config = Tracked::new(new_config);
+                        mod_undo.clear();
+                        self.cursor = 0;
+                        *page = 0;
+                        mod_page = mod_picker::show_page(mods, config, *page);
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        log!(Error, Config, "Couldn't switch profiles: {}", e);
+                        self.error = Some(e.with_hint());
                     }
                 }
-                2 => settings.btk_loaded_msg = !settings.btk_loaded_msg,
-                3 => settings.extra_rows = !settings.extra_rows,
-                _ => {}
+            }
+            MenuAction::ToggleConflict => {
+                if let Some(c) = conflicts.get_mut(self.cursor as usize) {
+                    c.disabled = !c.disabled;
+                }
+            }
+            MenuAction::InstallSaltwater => {
+                if let Some(c) = saltwater_status.updates.get(self.cursor as usize) {
+                    // Whatever did get changed shows up in the rescan
+                    self.error = match saltwater::install(c) {
+                        Ok(()) => None,
+                        Err(e) => {
+                            log!(Error, Launcher, "Couldn't install Saltwater: {}", e);
+                            Some(e.with_hint())
+                        }
+                    };
+                    *saltwater_status = saltwater::Status::scan();
+                    self.cursor = 0;
+                }
+            }
+            MenuAction::RollbackSaltwater => {
+                if saltwater_status.backup.is_none() {
+                    // Nothing to go back to, don't update the screen
+                    return Ok(());
+                }
+                self.error = match saltwater::rollback() {
+                    Ok(()) => None,
+                    Err(e) => {
+                        log!(Error, Launcher, "Couldn't roll back Saltwater: {}", e);
+                        Some(e.with_hint())
+                    }
+                };
+                *saltwater_status = saltwater::Status::scan();
+            }
+            MenuAction::ToggleSetting(c) => {
+                let language = settings.language.clone();
+                (c.advance)(settings);
+                if settings.language != language {
+                    i18n::set_language(settings);
+                }
+            }
+            MenuAction::CycleLaunchOption => {
+                // Even rows are a version's profile, odd rows its plugin
+                let row = self.cursor as usize;
+                let Some(ver) = versions.get(row / 2) else {
+                    return Ok(());
+                };
+                let key = ver.key();
+                // Taken out while picking, so a profile only this version uses can be
+                // replaced by the next new one instead of piling up new names
+                let mut options = settings.versions.remove(&key).unwrap_or_default();
+                if row % 2 == 0 {
+                    let mut choices = app::known_profiles(found_profiles, settings);
+                    choices.push(app::new_profile_name(&choices));
+                    options.profile = next_choice(&choices, options.profile.as_deref());
+                } else {
+                    options.plugin =
+                        next_choice(&saltwater_status.builds, options.plugin.as_deref());
+                }
+                settings.set_launch_options(key, options);
+            }
+            MenuAction::LogLevel(_) | MenuAction::LogCategory => {
+                match self.action {
+                    MenuAction::LogLevel(c) => {
+                        self.log_filter.level = self.log_filter.level.step(c)
+                    }
+                    _ => self.log_filter.next_category(),
+                }
+                self.cursor = log::lines(&self.log_filter).len().saturating_sub(1) as u32;
+            }
+            MenuAction::SaveLog => match log::dump() {
+                Ok(c) => {
+                    self.log_saved = Some(c);
+                    self.error = None;
+                }
+                Err(e) => {
+                    log!(Error, General, "Couldn't save the log: {}", e);
+                    self.log_saved = None;
+                    self.error = Some(e.with_hint());
+                }
             },
-            MenuAction::UpdateScreen => {}
+            // The prompt actions were already turned into what they do above
+            MenuAction::UpdateScreen
+            | MenuAction::Leave
+            | MenuAction::SaveChanges
+            | MenuAction::DiscardChanges
+            | MenuAction::KeepEditing => {}
             #[cfg(feature = "audio")]
             MenuAction::ToggleAudio => {}
         }
-        self.render(
-            console,
-            versions,
-            &mod_page,
-            *page,
-            mod_picker::num_pages(mods),
-            settings,
-        )
+        self.render(app, &mod_page)
     }
 }
@@ -0,0 +1,605 @@
+// Declarative definition of every submenu. Both MenuState::run and MenuState::view walk
+// these tables, so adding an entry here is all it takes to add it to the menu
+
+use std::fmt;
+
+use crate::{
+    format::barista_cfg::{BaristaConfig, SlotTitleMode},
+    i18n,
+};
+
+use super::{MenuAction, SubMenu};
+
+pub struct Menu {
+    /// Translation key for the title
+    pub title: &'static str,
+    /// Entries generated at runtime (game versions, mods) shown before `items`
+    pub list: ListKind,
+    pub items: &'static [MenuItem],
+    /// What leaving the submenu with B does
+    pub back: MenuAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListKind {
+    None,
+    Versions,
+    /// Game versions, launched without Saltwater
+    VanillaVersions,
+    /// Files in Luma's folders that could get in the way of Saltwater
+    Conflicts,
+    /// Saltwater builds in the updates folder
+    SaltwaterUpdates,
+    Mods,
+    /// Lines in the log
+    Log,
+    /// Profile and plugin of every game version, two rows each
+    LaunchOptions,
+}
+
+pub struct MenuItem {
+    /// Translation key for the label
+    pub label: &'static str,
+    pub kind: ItemKind,
+}
+
+pub enum ItemKind {
+    SubMenu(SubMenu),
+    Action(MenuAction),
+    /// An on/off [`Setting`]
+    Toggle(Setting),
+    /// A [`Setting`] with more than two values, advanced by one on every press
+    Cycle(Setting),
+}
+
+/// What the cursor is pointing at
+pub enum Entry<'a> {
+    /// Position in the runtime list
+    List(usize),
+    Item(&'a MenuItem),
+}
+
+/// A field of [`BaristaConfig`] that can be changed from the menu
+#[derive(Clone, Copy)]
+pub struct Setting {
+    /// Translation key for the explanation shown while the setting is highlighted
+    pub description: &'static str,
+    pub value: fn(&BaristaConfig) -> String,
+    /// Flips the setting, or moves it to its next value
+    pub advance: fn(&mut BaristaConfig),
+}
+
+// Function pointers don't compare reliably, but every setting has its own description
+impl PartialEq for Setting {
+    fn eq(&self, other: &Self) -> bool {
+        self.description == other.description
+    }
+}
+
+impl Eq for Setting {}
+
+impl fmt::Debug for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Setting").field(&self.description).finish()
+    }
+}
+
+const BACK_TO_MAIN: MenuItem = MenuItem {
+    label: "text_menu.back",
+    kind: ItemKind::SubMenu(SubMenu::Main),
+};
+
+const MAIN: Menu = Menu {
+    title: "text_menu.main_menu",
+    list: ListKind::None,
+    items: &[
+        MenuItem {
+            label: "text_menu.run",
+            kind: ItemKind::SubMenu(SubMenu::Run),
+        },
+        MenuItem {
+            label: "text_menu.setup",
+            kind: ItemKind::SubMenu(SubMenu::SetUp(false)),
+        },
+        #[cfg(feature = "audio")]
+        MenuItem {
+            label: "text_menu.music",
+            kind: ItemKind::SubMenu(SubMenu::Music),
+        },
+        MenuItem {
+            label: "text_menu.settings",
+            kind: ItemKind::SubMenu(SubMenu::Options),
+        },
+        MenuItem {
+            label: "text_menu.saltwater",
+            kind: ItemKind::SubMenu(SubMenu::Saltwater),
+        },
+        MenuItem {
+            label: "text_menu.credits",
+            kind: ItemKind::SubMenu(SubMenu::Credits),
+        },
+        MenuItem {
+            label: "text_menu.exit",
+            kind: ItemKind::Action(MenuAction::Exit),
+        },
+    ],
+    back: MenuAction::Exit,
+};
+
+const RUN: Menu = Menu {
+    title: "text_menu.run",
+    list: ListKind::Versions,
+    items: &[
+        MenuItem {
+            label: "text_menu.run_vanilla",
+            kind: ItemKind::SubMenu(SubMenu::RunVanilla),
+        },
+        MenuItem {
+            label: "text_menu.conflicts",
+            kind: ItemKind::SubMenu(SubMenu::Conflicts),
+        },
+        BACK_TO_MAIN,
+    ],
+    back: MenuAction::ChangeMenu(SubMenu::Main),
+};
+
+const RUN_VANILLA: Menu = Menu {
+    title: "text_menu.run_vanilla",
+    list: ListKind::VanillaVersions,
+    items: &[MenuItem {
+        label: "text_menu.back",
+        kind: ItemKind::SubMenu(SubMenu::Run),
+    }],
+    back: MenuAction::ChangeMenu(SubMenu::Run),
+};
+
+const CONFLICTS: Menu = Menu {
+    title: "text_menu.conflicts",
+    list: ListKind::Conflicts,
+    items: &[MenuItem {
+        label: "text_menu.back",
+        kind: ItemKind::SubMenu(SubMenu::Run),
+    }],
+    back: MenuAction::ChangeMenu(SubMenu::Run),
+};
+
+const SALTWATER: Menu = Menu {
+    title: "text_menu.saltwater",
+    list: ListKind::SaltwaterUpdates,
+    items: &[
+        MenuItem {
+            label: "saltwater.rollback",
+            kind: ItemKind::Action(MenuAction::RollbackSaltwater),
+        },
+        BACK_TO_MAIN,
+    ],
+    back: MenuAction::ChangeMenu(SubMenu::Main),
+};
+
+const SETUP: Menu = Menu {
+    title: "text_menu.setup",
+    list: ListKind::Mods,
+    items: &[
+        MenuItem {
+            label: "setup.prev_page",
+            kind: ItemKind::Action(MenuAction::ChangePage(false)),
+        },
+        MenuItem {
+            label: "setup.next_page",
+            kind: ItemKind::Action(MenuAction::ChangePage(true)),
+        },
+        MenuItem {
+            label: "text_menu.back",
+            kind: ItemKind::Action(MenuAction::Leave),
+        },
+    ],
+    back: MenuAction::Leave,
+};
+
+// Set up mods, when there's no mods to set up
+const SETUP_EMPTY: Menu = Menu {
+    title: "text_menu.setup",
+    list: ListKind::None,
+    items: &[MenuItem {
+        label: "text_menu.back",
+        kind: ItemKind::Action(MenuAction::Leave),
+    }],
+    back: MenuAction::Leave,
+};
+
+#[cfg(feature = "audio")]
+const MUSIC: Menu = Menu {
+    title: "text_menu.music",
+    list: ListKind::None,
+    items: &[
+        MenuItem {
+            label: "music.toggle",
+            kind: ItemKind::Action(MenuAction::ToggleAudio),
+        },
+        BACK_TO_MAIN,
+    ],
+    back: MenuAction::ChangeMenu(SubMenu::Main),
+};
+
+const OPTIONS: Menu = Menu {
+    title: "text_menu.settings",
+    list: ListKind::None,
+    items: &[
+        MenuItem {
+            label: "settings.original_gates",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.original_gates",
+                value: |c| on_off(c.original_gates),
+                advance: |c| c.original_gates = !c.original_gates,
+            }),
+        },
+        MenuItem {
+            label: "settings.slot_titles",
+            kind: ItemKind::Cycle(Setting {
+                description: "setting_info.slot_titles",
+                value: |c| match c.slot_titles {
+                    SlotTitleMode::Megamix => tr!("settings.slot_megamix"),
+                    SlotTitleMode::Original => tr!("settings.slot_original"),
+                    SlotTitleMode::Internal => tr!("settings.slot_internal"),
+                    SlotTitleMode::Infernal => tr!("settings.slot_infernal"),
+                },
+                advance: |c| {
+                    c.slot_titles = match c.slot_titles {
+                        SlotTitleMode::Megamix => SlotTitleMode::Original,
+                        SlotTitleMode::Original => SlotTitleMode::Internal,
+                        SlotTitleMode::Internal => SlotTitleMode::Infernal,
+                        SlotTitleMode::Infernal => SlotTitleMode::Megamix,
+                    }
+                },
+            }),
+        },
+        MenuItem {
+            label: "settings.btk_loaded_msg",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.btk_loaded_msg",
+                value: |c| on_off(c.btk_loaded_msg),
+                advance: |c| c.btk_loaded_msg = !c.btk_loaded_msg,
+            }),
+        },
+        MenuItem {
+            label: "settings.extra_rows",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.extra_rows",
+                value: |c| on_off(c.extra_rows),
+                advance: |c| c.extra_rows = !c.extra_rows,
+            }),
+        },
+        MenuItem {
+            label: "settings.restore_rhmpatch",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.restore_rhmpatch",
+                value: |c| on_off(c.restore_rhmpatch),
+                advance: |c| c.restore_rhmpatch = !c.restore_rhmpatch,
+            }),
+        },
+        MenuItem {
+            label: "settings.restore_plgldr",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.restore_plgldr",
+                value: |c| on_off(c.restore_plgldr),
+                advance: |c| c.restore_plgldr = !c.restore_plgldr,
+            }),
+        },
+        MenuItem {
+            label: "settings.quick_launch",
+            kind: ItemKind::Toggle(Setting {
+                description: "setting_info.quick_launch",
+                value: |c| on_off(c.quick_launch),
+                advance: |c| c.quick_launch = !c.quick_launch,
+            }),
+        },
+        MenuItem {
+            label: "settings.language",
+            kind: ItemKind::Cycle(Setting {
+                description: "setting_info.language",
+                value: |c| {
+                    i18n::LANGUAGES
+                        .iter()
+                        .find(|(code, _)| Some(*code) == c.language.as_deref())
+                        .map(|(_, name)| name.to_string())
+                        .unwrap_or_else(|| tr!("settings.language_system"))
+                },
+                // System language -> every available language -> system language. Loading
+                // the new string table is up to whoever changed it
+                advance: |c| {
+                    let next = match &c.language {
+                        None => Some(0),
+                        Some(c) => i18n::LANGUAGES
+                            .iter()
+                            .position(|(code, _)| code == c)
+                            .map(|i| i + 1)
+                            .filter(|i| *i < i18n::LANGUAGES.len()),
+                    };
+                    c.language = next.map(|i| i18n::LANGUAGES[i].0.to_string());
+                },
+            }),
+        },
+        MenuItem {
+            label: "settings.launch_options",
+            kind: ItemKind::SubMenu(SubMenu::LaunchOptions),
+        },
+        MenuItem {
+            label: "text_menu.back",
+            kind: ItemKind::Action(MenuAction::Leave),
+        },
+    ],
+    back: MenuAction::Leave,
+};
+
+// Changes here are saved along with the rest of Settings
+const LAUNCH_OPTIONS: Menu = Menu {
+    title: "text_menu.launch_options",
+    list: ListKind::LaunchOptions,
+    items: &[MenuItem {
+        label: "text_menu.back",
+        kind: ItemKind::SubMenu(SubMenu::Options),
+    }],
+    back: MenuAction::ChangeMenu(SubMenu::Options),
+};
+
+const CREDITS: Menu = Menu {
+    title: "text_menu.credits",
+    list: ListKind::None,
+    items: &[BACK_TO_MAIN],
+    back: MenuAction::ChangeMenu(SubMenu::Main),
+};
+
+const UNSAVED: Menu = Menu {
+    title: "unsaved.title",
+    list: ListKind::None,
+    items: &[
+        MenuItem {
+            label: "unsaved.save",
+            kind: ItemKind::Action(MenuAction::SaveChanges),
+        },
+        MenuItem {
+            label: "unsaved.discard",
+            kind: ItemKind::Action(MenuAction::DiscardChanges),
+        },
+        MenuItem {
+            label: "unsaved.cancel",
+            kind: ItemKind::Action(MenuAction::KeepEditing),
+        },
+    ],
+    back: MenuAction::KeepEditing,
+};
+
+const LOG: Menu = Menu {
+    title: "text_menu.log",
+    list: ListKind::Log,
+    items: &[
+        MenuItem {
+            label: "log.save",
+            kind: ItemKind::Action(MenuAction::SaveLog),
+        },
+        BACK_TO_MAIN,
+    ],
+    back: MenuAction::ChangeMenu(SubMenu::Main),
+};
+
+impl SubMenu {
+    /// Definition of this submenu, given how many entries its runtime list has
+    pub fn menu(&self, list_len: usize) -> &'static Menu {
+        match self {
+            SubMenu::Main => &MAIN,
+            SubMenu::Run => &RUN,
+            SubMenu::RunVanilla => &RUN_VANILLA,
+            SubMenu::Conflicts => &CONFLICTS,
+            SubMenu::Saltwater => &SALTWATER,
+            SubMenu::SetUp(_) if list_len == 0 => &SETUP_EMPTY,
+            SubMenu::SetUp(_) => &SETUP,
+            #[cfg(feature = "audio")]
+            SubMenu::Music => &MUSIC,
+            SubMenu::Options => &OPTIONS,
+            SubMenu::LaunchOptions => &LAUNCH_OPTIONS,
+            SubMenu::Credits => &CREDITS,
+            SubMenu::Unsaved(_) => &UNSAVED,
+            SubMenu::Log => &LOG,
+        }
+    }
+}
+
+impl Menu {
+    pub fn len(&self, list_len: usize) -> usize {
+        match self.list {
+            ListKind::None => self.items.len(),
+            _ => list_len + self.items.len(),
+        }
+    }
+
+    pub fn entry(&self, cursor: usize, list_len: usize) -> Option<Entry> {
+        let list_len = match self.list {
+            ListKind::None => 0,
+            _ => list_len,
+        };
+        if cursor < list_len {
+            Some(Entry::List(cursor))
+        } else {
+            self.items.get(cursor - list_len).map(Entry::Item)
+        }
+    }
+
+    /// What choosing the entry under the cursor does
+    pub fn action(&self, cursor: usize, list_len: usize) -> MenuAction {
+        match self.entry(cursor, list_len) {
+            Some(Entry::List(_)) => match self.list {
+                ListKind::Versions => MenuAction::Run,
+                ListKind::VanillaVersions => MenuAction::RunVanilla,
+                ListKind::Conflicts => MenuAction::ToggleConflict,
+                ListKind::SaltwaterUpdates => MenuAction::InstallSaltwater,
+                ListKind::Mods => MenuAction::ToggleMod,
+                ListKind::LaunchOptions => MenuAction::CycleLaunchOption,
+                ListKind::Log | ListKind::None => MenuAction::None,
+            },
+            Some(Entry::Item(item)) => item.kind.action(),
+            None => MenuAction::None,
+        }
+    }
+}
+
+impl MenuItem {
+    pub fn label(&self, settings: &BaristaConfig) -> String {
+        match &self.kind {
+            ItemKind::Toggle(c) | ItemKind::Cycle(c) => {
+                format!("{}: {}", i18n::tr(self.label), (c.value)(settings))
+            }
+            _ => i18n::tr(self.label),
+        }
+    }
+}
+
+impl ItemKind {
+    pub fn action(&self) -> MenuAction {
+        match self {
+            ItemKind::SubMenu(c) => MenuAction::ChangeMenu(*c),
+            ItemKind::Action(c) => c.clone(),
+            ItemKind::Toggle(c) | ItemKind::Cycle(c) => MenuAction::ToggleSetting(*c),
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value {
+        tr!("settings.on")
+    } else {
+        tr!("settings.off")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What choosing every entry does, in order, plus one past the end
+    fn actions(menu: &Menu, list_len: usize) -> Vec<MenuAction> {
+        (0..=menu.len(list_len))
+            .map(|c| menu.action(c, list_len))
+            .collect()
+    }
+
+    #[test]
+    fn run_lists_versions_before_items() {
+        let menu = SubMenu::Run.menu(2);
+        assert_eq!(menu.title, RUN.title);
+        assert_eq!(menu.len(2), 5);
+        assert!(matches!(menu.entry(1, 2), Some(Entry::List(1))));
+        assert!(matches!(menu.entry(2, 2), Some(Entry::Item(_))));
+        assert!(menu.entry(5, 2).is_none());
+        assert_eq!(
+            actions(menu, 2),
+            [
+                MenuAction::Run,
+                MenuAction::Run,
+                MenuAction::ChangeMenu(SubMenu::RunVanilla),
+                MenuAction::ChangeMenu(SubMenu::Conflicts),
+                MenuAction::ChangeMenu(SubMenu::Main),
+                MenuAction::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn run_without_versions() {
+        let menu = SubMenu::Run.menu(0);
+        assert_eq!(menu.len(0), 3);
+        assert_eq!(
+            menu.action(0, 0),
+            MenuAction::ChangeMenu(SubMenu::RunVanilla)
+        );
+    }
+
+    #[test]
+    fn setup_lists_mods_before_page_controls() {
+        let menu = SubMenu::SetUp(false).menu(3);
+        assert_eq!(menu.list, ListKind::Mods);
+        assert_eq!(SubMenu::SetUp(true).menu(3).list, ListKind::Mods);
+        assert_eq!(
+            actions(menu, 3),
+            [
+                MenuAction::ToggleMod,
+                MenuAction::ToggleMod,
+                MenuAction::ToggleMod,
+                MenuAction::ChangePage(false),
+                MenuAction::ChangePage(true),
+                MenuAction::Leave,
+                MenuAction::None,
+            ]
+        );
+        assert_eq!(menu.back, MenuAction::Leave);
+    }
+
+    #[test]
+    fn setup_without_mods_is_setup_empty() {
+        let menu = SubMenu::SetUp(false).menu(0);
+        assert_eq!(menu.list, ListKind::None);
+        assert_eq!(menu.items.len(), SETUP_EMPTY.items.len());
+        assert_eq!(actions(menu, 0), [MenuAction::Leave, MenuAction::None]);
+    }
+
+    #[test]
+    fn options_ignores_the_list() {
+        let menu = SubMenu::Options.menu(5);
+        assert_eq!(menu.title, OPTIONS.title);
+        assert_eq!(menu.len(5), 10);
+        assert!(matches!(menu.entry(0, 5), Some(Entry::Item(_))));
+        let actions: Vec<_> = actions(menu, 5)
+            .into_iter()
+            .map(|c| match c {
+                MenuAction::ToggleSetting(c) => c.description.to_string(),
+                c => format!("{:?}", c),
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                "setting_info.original_gates",
+                "setting_info.slot_titles",
+                "setting_info.btk_loaded_msg",
+                "setting_info.extra_rows",
+                "setting_info.restore_rhmpatch",
+                "setting_info.restore_plgldr",
+                "setting_info.quick_launch",
+                "setting_info.language",
+                "ChangeMenu(LaunchOptions)",
+                "Leave",
+                "None",
+            ]
+        );
+    }
+
+    #[test]
+    fn launch_options_go_back_to_settings() {
+        let menu = SubMenu::LaunchOptions.menu(4);
+        assert_eq!(menu.list, ListKind::LaunchOptions);
+        assert_eq!(
+            actions(menu, 4),
+            [
+                MenuAction::CycleLaunchOption,
+                MenuAction::CycleLaunchOption,
+                MenuAction::CycleLaunchOption,
+                MenuAction::CycleLaunchOption,
+                MenuAction::ChangeMenu(SubMenu::Options),
+                MenuAction::None,
+            ]
+        );
+        assert_eq!(menu.back, MenuAction::ChangeMenu(SubMenu::Options));
+    }
+
+    #[test]
+    fn settings_advance_and_show_their_value() {
+        let mut settings = BaristaConfig::default();
+        for item in OPTIONS.items {
+            let (ItemKind::Toggle(c) | ItemKind::Cycle(c)) = &item.kind else {
+                continue;
+            };
+            let before = (c.value)(&settings);
+            (c.advance)(&mut settings);
+            assert_ne!((c.value)(&settings), before, "{}", c.description);
+        }
+    }
+}
@@ -1,5 +1,7 @@
+pub mod bottom_screen;
 pub mod menu;
 pub mod top_screen;
 
 //pub use menu::MenuState;
+pub use bottom_screen::bottom_screen_scene;
 pub use top_screen::top_screen_scene;
@@ -0,0 +1,39 @@
+// Version numbers the way Luma3DS packs them into a u32, which its plugin loader and 3gxtool
+// copied: major.minor.revision in the top 3 bytes, the lowest one unused
+
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub revision: u8,
+}
+
+impl Version {
+    pub const fn new(major: u8, minor: u8, revision: u8) -> Self {
+        Self {
+            major,
+            minor,
+            revision,
+        }
+    }
+
+    pub const fn from_packed(packed: u32) -> Self {
+        Self::new(
+            (packed >> 24) as u8,
+            (packed >> 16) as u8,
+            (packed >> 8) as u8,
+        )
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v{}.{}", self.major, self.minor)?;
+        if self.revision != 0 {
+            write!(f, ".{}", self.revision)?;
+        }
+        Ok(())
+    }
+}